@@ -0,0 +1,40 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fs::File;
+use std::io::{self, BufReader};
+
+#[derive(Serialize, Deserialize)]
+pub struct Record {
+    pub url: String,
+    pub archived: DateTime<Utc>,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    #[serde(serialize_with = "encode_body", deserialize_with = "decode_body")]
+    pub body: Vec<u8>,
+}
+
+pub fn write(filename: &str, record: &Record) -> io::Result<()> {
+    let mut encoder = GzEncoder::new(File::create(filename)?, Compression::default());
+    serde_json::to_writer(&mut encoder, record)?;
+    encoder.finish()?;
+    Ok(())
+}
+
+pub fn read(filename: &str) -> io::Result<Record> {
+    let decoder = GzDecoder::new(BufReader::new(File::open(filename)?));
+    Ok(serde_json::from_reader(decoder)?)
+}
+
+fn encode_body<S: Serializer>(body: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&STANDARD.encode(body))
+}
+
+fn decode_body<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    STANDARD.decode(text).map_err(serde::de::Error::custom)
+}
@@ -0,0 +1,97 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+
+const UNBLOCKED_HOSTS: &[&str] = &["localhost", "localhost.localdomain", "local", "0.0.0.0"];
+
+#[derive(Debug, Default, Clone)]
+pub struct Blocklist {
+    hosts: HashSet<String>,
+    allowed: HashSet<String>,
+}
+
+impl Blocklist {
+    pub fn load(path: &str) -> io::Result<Self> {
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let mut hosts = HashSet::new();
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("");
+            let mut fields = line.split_whitespace();
+            let first = match fields.next() {
+                Some(first) => first,
+                None => continue,
+            };
+            let names: Vec<&str> = if first.parse::<std::net::IpAddr>().is_ok() {
+                fields.collect()
+            } else {
+                vec![first]
+            };
+            hosts.extend(
+                names
+                    .into_iter()
+                    .map(|name| name.trim_end_matches('.').to_ascii_lowercase())
+                    .filter(|name| !UNBLOCKED_HOSTS.contains(&name.as_str())),
+            );
+        }
+        Blocklist {
+            hosts,
+            allowed: HashSet::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.hosts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hosts.is_empty()
+    }
+
+    pub fn allow(&mut self, host: &str) {
+        self.allowed
+            .insert(host.trim_end_matches('.').to_ascii_lowercase());
+    }
+
+    pub fn blocks(&self, host: &str) -> bool {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        if self.allowed.contains(&host) {
+            return false;
+        }
+        let mut domain = host.as_str();
+        loop {
+            if self.hosts.contains(domain) {
+                return true;
+            }
+            match domain.split_once('.') {
+                Some((_, parent)) => domain = parent,
+                None => return false,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hosts_entries_block_their_subdomains() {
+        let list = Blocklist::parse(
+            "# comment\n127.0.0.1 localhost\n0.0.0.0 tracker.example ads.example # inline\nplain.example\n",
+        );
+        assert_eq!(list.len(), 3);
+        assert!(list.blocks("tracker.example"));
+        assert!(list.blocks("cdn.ads.example"));
+        assert!(list.blocks("PLAIN.example."));
+        assert!(!list.blocks("example"));
+        assert!(!list.blocks("localhost"));
+
+        let mut list = list;
+        list.allow("cdn.ads.example");
+        assert!(!list.blocks("cdn.ads.example"));
+        assert!(list.blocks("ads.example"));
+    }
+}
@@ -0,0 +1,983 @@
+use crossterm::{
+    cursor,
+    event::{read, Event, KeyCode, KeyEventKind},
+    execute,
+    style::{Color, ResetColor, SetBackgroundColor, SetForegroundColor},
+    terminal::{self, disable_raw_mode, enable_raw_mode, Clear, ClearType},
+};
+use reqwest::header::HeaderValue;
+use std::error::Error;
+use std::io::{self, IsTerminal, Write};
+
+use crate::config::Proxies;
+use crate::keys::Action;
+use crate::theme::{self, Theme};
+use crate::{prompt, quickmark_key, Browser, Credential, UA_PRESETS};
+
+const HELP_CHROME_ROWS: usize = 14;
+const HELP_NOTES: &[&str] = &[
+    ": opens the command palette.",
+    "Key bindings can be changed with `keymap` and [keys] in config.toml.",
+];
+
+pub type Handler = fn(&mut Browser, &str) -> Result<(), Box<dyn Error>>;
+
+pub struct Command {
+    pub name: &'static str,
+    pub args: &'static str,
+    pub description: &'static str,
+    aliases: &'static [&'static str],
+    handler: Handler,
+}
+
+const fn command(
+    name: &'static str,
+    args: &'static str,
+    description: &'static str,
+    handler: Handler,
+) -> Command {
+    Command {
+        name,
+        args,
+        description,
+        aliases: &[],
+        handler,
+    }
+}
+
+pub const COMMANDS: &[Command] = &[
+    command(
+        "g",
+        "URL",
+        "Go to URL or local path (g !BANG QUERY searches: !g, !ddg, !w)",
+        |browser, url| browser.navigate(url),
+    ),
+    command(
+        "go",
+        "",
+        "Open the address bar with history and bookmark suggestions (Ctrl-L)",
+        |browser, _| browser.omnibox(),
+    ),
+    command(
+        "o",
+        "NUMBER",
+        "Follow link NUMBER on the page",
+        |browser, number| match number.parse::<usize>() {
+            Ok(index) => browser.follow_link(index),
+            Err(_) => usage("o NUMBER"),
+        },
+    ),
+    command(
+        "f",
+        "",
+        "Label the links on screen with letters and follow one by typing its label",
+        |browser, _| browser.follow_hint(false),
+    ),
+    command(
+        "F",
+        "",
+        "Like f, but open the link in a new tab",
+        |browser, _| browser.follow_hint(true),
+    ),
+    command(
+        "links",
+        "",
+        "List every link on the page and follow one",
+        |browser, _| browser.show_links(),
+    ),
+    command(
+        "next",
+        "",
+        "Follow the page's next page link",
+        |browser, _| browser.follow_pagination(true),
+    ),
+    command(
+        "prev",
+        "",
+        "Follow the page's previous page link",
+        |browser, _| browser.follow_pagination(false),
+    ),
+    command(
+        "yank",
+        "[NUMBER]",
+        "Copy the page URL (or link NUMBER) to the clipboard",
+        yank,
+    ),
+    command(
+        "open",
+        "",
+        "Open the current page in the system default browser",
+        |browser, _| {
+            browser.open_externally();
+            Ok(())
+        },
+    ),
+    command(
+        "post",
+        "URL",
+        "Send a POST request with a body",
+        |browser, url| browser.prompt_post(url),
+    ),
+    command(
+        "resend",
+        "",
+        "Repeat the last POST request",
+        |browser, _| browser.resend(),
+    ),
+    command("t", "[URL]", "Open a new tab", |browser, url| {
+        browser.new_tab(Some(url).filter(|url| !url.is_empty()))
+    }),
+    command("tn", "", "Switch to the next tab", |browser, _| {
+        Ok(browser.switch_tab(1)?)
+    }),
+    command("tp", "", "Switch to the previous tab", |browser, _| {
+        Ok(browser.switch_tab(-1)?)
+    }),
+    command("tc", "", "Close the current tab", |browser, _| {
+        Ok(browser.close_tab()?)
+    }),
+    command(
+        "reopen",
+        "",
+        "Reopen the most recently closed tab or page you navigated away from",
+        |browser, _| browser.reopen(),
+    ),
+    command("b", "", "Show bookmarks", |browser, _| {
+        browser.show_bookmarks()
+    }),
+    command(
+        "mark",
+        "LETTER",
+        "Assign a quickmark letter to the current page's bookmark",
+        |browser, letter| match quickmark_key(letter) {
+            Some(key) => Ok(browser.mark_current_page(key)?),
+            None => usage("mark LETTER"),
+        },
+    ),
+    command(
+        "'",
+        "LETTER",
+        "Open the bookmark marked with LETTER",
+        |browser, letter| match quickmark_key(letter) {
+            Some(key) => browser.jump_to_quickmark(key),
+            None => usage("'LETTER"),
+        },
+    ),
+    command(
+        "a",
+        "[FOLDER/][TITLE]",
+        "Add current page to bookmarks (defaults to the page title)",
+        |browser, title| Ok(browser.add_bookmark(title)?),
+    ),
+    command(
+        "export-bookmarks",
+        "FILE",
+        "Export bookmarks as Netscape HTML",
+        |browser, file| Ok(browser.export_bookmarks(file)?),
+    ),
+    command(
+        "import-bookmarks",
+        "FILE",
+        "Import bookmarks from Netscape HTML",
+        |browser, file| Ok(browser.import_bookmarks(file)?),
+    ),
+    command(
+        "clean-bookmarks",
+        "[check]",
+        "Remove duplicate bookmarks (check also finds 404s and offers to delete them)",
+        |browser, check| match check {
+            "" | "check" => Ok(browser.clean_bookmarks(check == "check")?),
+            _ => usage("clean-bookmarks [check]"),
+        },
+    ),
+    command("history", "", "Show history", |browser, _| {
+        browser.show_history()
+    }),
+    command(
+        "ql",
+        "",
+        "Add the current page to the read-later queue",
+        |browser, _| Ok(browser.read_later_add()?),
+    ),
+    command("queue", "", "Show the read-later queue", |browser, _| {
+        browser.show_queue()
+    }),
+    command("top", "", "Show the most visited pages", |browser, _| {
+        browser.show_top()
+    }),
+    command(
+        "restore",
+        "",
+        "Reopen the tabs saved when the browser last quit",
+        |browser, _| {
+            browser.restore_session()?;
+            Ok(())
+        },
+    ),
+    command("toc", "", "Show table of contents", |browser, _| {
+        Ok(browser.show_toc()?)
+    }),
+    command(
+        "forms",
+        "",
+        "List forms on the page and submit one",
+        |browser, _| browser.show_forms(),
+    ),
+    command(
+        "cookies",
+        "",
+        "Show cookies for the current site",
+        |browser, _| Ok(browser.show_cookies()?),
+    ),
+    command(
+        "headers",
+        "",
+        "Show response headers for the current page",
+        |browser, _| Ok(browser.show_headers()?),
+    ),
+    command(
+        "curl",
+        "",
+        "Print a curl command that repeats the last request",
+        |browser, _| {
+            match browser.curl_command() {
+                Some(command) => println!("{}", command),
+                None => println!("No request has been sent from this tab."),
+            }
+            Ok(())
+        },
+    ),
+    command(
+        "tls",
+        "",
+        "Show the server certificate for the current page",
+        |browser, _| Ok(browser.show_tls()?),
+    ),
+    command("back", "", "Go back to the previous page", |browser, _| {
+        browser.perform(Action::Back, "")
+    }),
+    command(
+        "forward",
+        "",
+        "Go forward to the next page",
+        |browser, _| browser.perform(Action::Forward, ""),
+    ),
+    command("r", "", "Reload current page", |browser, _| {
+        browser.perform(Action::Reload, "")
+    }),
+    command("R", "", "Hard reload, bypassing the cache", |browser, _| {
+        browser.perform(Action::HardReload, "")
+    }),
+    command("source", "", "View page source", |browser, _| {
+        Ok(browser.view_page_source()?)
+    }),
+    command("raw", "", "Toggle raw mode view", |browser, _| {
+        Ok(browser.toggle_raw_mode()?)
+    }),
+    command(
+        "view-as",
+        "TYPE",
+        "Re-render the page as html, json, text, xml or markdown (auto undoes it)",
+        |browser, kind| browser.view_as(kind),
+    ),
+    command(
+        "reader",
+        "",
+        "Toggle reader mode for articles",
+        |browser, _| Ok(browser.toggle_reader_mode()?),
+    ),
+    command(
+        "json",
+        "",
+        "Explore a JSON response as a collapsible tree",
+        |browser, _| Ok(browser.explore_json()?),
+    ),
+    command(
+        "json-pretty",
+        "",
+        "Toggle between the JSON tree and plain pretty-print",
+        |browser, _| Ok(browser.toggle_json_pretty()?),
+    ),
+    command(
+        "jq",
+        "PATH",
+        "Show only the part of a JSON response at PATH, e.g. .items[0].name",
+        |browser, path| Ok(browser.query_json(path)?),
+    ),
+    command(
+        "download",
+        "FILENAME",
+        "Download current page in the background",
+        |browser, filename| browser.download_page(filename),
+    ),
+    command(
+        "downloads",
+        "",
+        "Show active and finished downloads",
+        |browser, _| Ok(browser.show_downloads()?),
+    ),
+    command(
+        "save-md",
+        "FILENAME",
+        "Save current page as Markdown",
+        |browser, filename| browser.save_markdown(filename),
+    ),
+    command(
+        "snapshot",
+        "FILENAME",
+        "Save the article as a standalone, styled HTML file",
+        |browser, filename| browser.save_snapshot(filename),
+    ),
+    command(
+        "archive",
+        "FILENAME",
+        "Archive the page with its headers (gzipped JSON)",
+        |browser, filename| Ok(browser.archive_page(filename)?),
+    ),
+    command(
+        "archive-view",
+        "FILENAME",
+        "Open an archived page offline",
+        |browser, filename| browser.view_archive(filename),
+    ),
+    command(
+        "timeout",
+        "[SECS]",
+        "Show or set the request timeout",
+        timeout,
+    ),
+    command(
+        "redirects",
+        "[MAX]",
+        "Show or set the maximum number of redirects to follow",
+        redirects,
+    ),
+    command(
+        "numbers",
+        "",
+        "Show or hide the line-number gutter",
+        |browser, _| {
+            browser.show_numbers = !browser.show_numbers;
+            Ok(browser.display_page()?)
+        },
+    ),
+    command(
+        "accept",
+        "[TYPE|off]",
+        "Show or set the Accept header sent with requests",
+        accept,
+    ),
+    command(
+        "ua",
+        "[desktop|mobile|bot|STRING]",
+        "Show or switch the User-Agent",
+        user_agent,
+    ),
+    command(
+        "resolve",
+        "HOST",
+        "Show the IP addresses a host resolves to",
+        resolve,
+    ),
+    command(
+        "theme",
+        "[dark|light]",
+        "Show or switch the color theme",
+        switch_theme,
+    ),
+    command(
+        "auth",
+        "[USER[:PASS]]",
+        "Use basic auth for the current host, asking for what is missing (auth off to forget)",
+        auth,
+    ),
+    command(
+        "token",
+        "[TOKEN]",
+        "Use a bearer token for the current host, asking for it if not given",
+        token,
+    ),
+    command(
+        "proxy",
+        "[[http|https] URL]",
+        "Show or set a proxy (proxy off to disable)",
+        proxy,
+    ),
+    command(
+        "blocklist",
+        "",
+        "Show how many hosts the tracker blocklist blocks",
+        |browser, _| {
+            browser.show_blocklist();
+            Ok(())
+        },
+    ),
+    command("reload-config", "", "Re-read config.toml", |browser, _| {
+        browser.reload_config();
+        println!("Configuration reloaded.");
+        Ok(())
+    }),
+    command(
+        "cache-clear",
+        "",
+        "Drop all cached responses",
+        |browser, _| {
+            browser.clear_cache();
+            println!("Cache cleared.");
+            Ok(())
+        },
+    ),
+    command(
+        "profile",
+        "[NAME]",
+        "Show or switch the profile (separate bookmarks, history and cookies)",
+        profile,
+    ),
+    command(
+        "incognito",
+        "",
+        "Toggle private browsing (no history, cache or saved cookies)",
+        incognito,
+    ),
+    command(
+        "search",
+        "QUERY",
+        "Search in current page (/PATTERN/ for a regex)",
+        |browser, query| browser.perform(Action::Search, query),
+    ),
+    command(
+        "search-clear",
+        "",
+        "Remove search highlighting",
+        |browser, _| Ok(browser.clear_search()?),
+    ),
+    command(
+        "search-case",
+        "",
+        "Toggle case-sensitive search",
+        |browser, _| {
+            browser.case_sensitive = !browser.case_sensitive;
+            if browser.case_sensitive {
+                println!("Search is now case-sensitive.");
+            } else {
+                println!("Search is now case-insensitive.");
+            }
+            Ok(())
+        },
+    ),
+    command(
+        "search-all",
+        "QUERY",
+        "Search every open tab and recently cached page",
+        |browser, query| browser.search_all(query),
+    ),
+    command("n", "", "Jump to next search match", |browser, _| {
+        browser.perform(Action::NextMatch, "")
+    }),
+    command("N", "", "Jump to previous search match", |browser, _| {
+        browser.perform(Action::PreviousMatch, "")
+    }),
+    command("w", "", "Scroll up", |browser, _| {
+        browser.perform(Action::ScrollUp, "")
+    }),
+    command("s", "", "Scroll down", |browser, _| {
+        browser.perform(Action::ScrollDown, "")
+    }),
+    command("u", "", "Scroll up half a page", |browser, _| {
+        browser.perform(Action::HalfPageUp, "")
+    }),
+    command("d", "", "Scroll down half a page", |browser, _| {
+        browser.perform(Action::HalfPageDown, "")
+    }),
+    command("gg", "", "Go to top of page", |browser, _| {
+        browser.perform(Action::Top, "")
+    }),
+    command("G", "", "Go to bottom of page", |browser, _| {
+        browser.perform(Action::Bottom, "")
+    }),
+    command(
+        "line",
+        "N",
+        "Jump to line N (also :N)",
+        |browser, number| match number.parse::<usize>() {
+            Ok(line) => Ok(browser.go_to_line(line)?),
+            Err(_) => usage("line NUMBER (or :NUMBER)"),
+        },
+    ),
+    command(
+        "scroll-step",
+        "[N]",
+        "Show or set how many lines w and s scroll",
+        |browser, step| {
+            if step.is_empty() {
+                println!("Scrolling {} lines at a time.", browser.config.scroll_step);
+            } else {
+                browser.set_scroll_step(step);
+            }
+            Ok(())
+        },
+    ),
+    command("smooth", "", "Toggle smooth scrolling", |browser, _| {
+        browser.toggle_smooth_scroll();
+        Ok(())
+    }),
+    command(
+        "help",
+        "",
+        "Show this help, a page at a time (h)",
+        |_, _| Ok(help()?),
+    )
+    .with_aliases(&["h"]),
+    command("quit", "", "Quit (q)", |browser, _| {
+        browser.perform(Action::Quit, "")
+    })
+    .with_aliases(&["q"]),
+];
+
+/// Splits a prompt line into its verb and argument. Quickmarks (`'a`) and
+/// line jumps (`:12`) take their argument without a space.
+pub fn parse(input: &str) -> (&str, &str) {
+    if let Some(letter) = input.strip_prefix('\'') {
+        return ("'", letter.trim());
+    }
+    if let Some(line) = input.strip_prefix(':') {
+        return ("line", line.trim());
+    }
+    match input.split_once(char::is_whitespace) {
+        Some((verb, argument)) => (verb, argument.trim()),
+        None => (input, ""),
+    }
+}
+
+pub fn find(verb: &str) -> Option<&'static Command> {
+    COMMANDS
+        .iter()
+        .find(|command| command.name == verb || command.aliases.contains(&verb))
+}
+
+/// Runs a command, showing its usage when a required argument is missing
+/// and reporting any error without leaving the browser.
+pub fn run(browser: &mut Browser, command: &Command, argument: &str) -> io::Result<()> {
+    if argument.is_empty() && !command.args.is_empty() && !command.args.starts_with('[') {
+        println!("Usage: {}", command.usage());
+        return Ok(());
+    }
+    match (command.handler)(browser, argument) {
+        Ok(()) => Ok(()),
+        Err(e) => browser.report_error(&e),
+    }
+}
+
+fn usage(text: &str) -> Result<(), Box<dyn Error>> {
+    println!("Usage: {}", text);
+    Ok(())
+}
+
+fn yank(browser: &mut Browser, number: &str) -> Result<(), Box<dyn Error>> {
+    if number.is_empty() {
+        browser.yank(None);
+        return Ok(());
+    }
+    match number.parse::<usize>() {
+        Ok(index) => browser.yank(Some(index)),
+        Err(_) => return usage("yank [NUMBER]"),
+    }
+    Ok(())
+}
+
+fn timeout(browser: &mut Browser, secs: &str) -> Result<(), Box<dyn Error>> {
+    if secs.is_empty() {
+        println!("Request timeout: {}s", browser.options.timeout.as_secs());
+        return Ok(());
+    }
+    match secs.parse::<u64>() {
+        Ok(secs) if secs > 0 => {
+            browser.set_timeout(secs);
+            println!("Request timeout set to {}s", secs);
+            Ok(())
+        }
+        _ => usage("timeout SECS"),
+    }
+}
+
+fn redirects(browser: &mut Browser, max: &str) -> Result<(), Box<dyn Error>> {
+    if max.is_empty() {
+        println!("Maximum redirects: {}", browser.options.max_redirects);
+        return Ok(());
+    }
+    match max.parse::<usize>() {
+        Ok(max) => {
+            browser.set_max_redirects(max);
+            println!("Maximum redirects set to {}", max);
+            Ok(())
+        }
+        Err(_) => usage("redirects MAX"),
+    }
+}
+
+fn accept(browser: &mut Browser, accept: &str) -> Result<(), Box<dyn Error>> {
+    match accept {
+        "" => match browser
+            .accept
+            .as_ref()
+            .and_then(|accept| accept.to_str().ok())
+        {
+            Some(accept) => println!("Accept: {}", accept),
+            None => println!("Accept: not set (client default)"),
+        },
+        "off" => {
+            browser.accept = None;
+            println!("Accept header cleared.");
+        }
+        accept => {
+            browser.accept = Some(HeaderValue::from_str(accept)?);
+            println!("Sending Accept: {} with every request", accept);
+        }
+    }
+    Ok(())
+}
+
+fn user_agent(browser: &mut Browser, user_agent: &str) -> Result<(), Box<dyn Error>> {
+    if user_agent.is_empty() {
+        println!("User-Agent: {}", browser.options.user_agent);
+        let presets: Vec<&str> = UA_PRESETS.iter().map(|(name, _)| *name).collect();
+        println!("Presets: {} (or any custom string)", presets.join(", "));
+    } else {
+        browser.set_user_agent(user_agent);
+        println!("User-Agent set to {}", browser.options.user_agent);
+    }
+    Ok(())
+}
+
+fn resolve(browser: &mut Browser, host: &str) -> Result<(), Box<dyn Error>> {
+    let addresses = browser.lookup(host)?;
+    let resolver = browser
+        .config
+        .doh_url
+        .as_deref()
+        .unwrap_or("system resolver");
+    println!("{} via {}:", host, resolver);
+    for address in addresses {
+        println!("  {}", address);
+    }
+    Ok(())
+}
+
+fn switch_theme(browser: &mut Browser, name: &str) -> Result<(), Box<dyn Error>> {
+    if name.is_empty() {
+        println!("Theme: {}", browser.theme.name);
+        println!("Available: {}", theme::THEMES.join(", "));
+        return Ok(());
+    }
+    browser.theme = Theme::load(name, &browser.config.colors)?;
+    Ok(browser.display_page()?)
+}
+
+fn auth(browser: &mut Browser, login: &str) -> Result<(), Box<dyn Error>> {
+    if login == "off" {
+        let host = browser.set_credential(None)?;
+        println!("Forgot credentials for {}", host);
+        return Ok(());
+    }
+    let login = match login.split_once(':') {
+        Some((user, password)) => Some((user.to_string(), password.to_string())),
+        None => Browser::read_basic_auth(login)?,
+    };
+    if let Some((user, password)) = login {
+        let credential = Credential::Basic {
+            user: user.clone(),
+            password,
+        };
+        let host = browser.set_credential(Some(credential))?;
+        println!("Using basic auth as {} for {}", user, host);
+    }
+    Ok(())
+}
+
+fn token(browser: &mut Browser, token: &str) -> Result<(), Box<dyn Error>> {
+    let token = match token {
+        "" => prompt::read_secret("Token (empty to cancel): ")?,
+        token => token.to_string(),
+    };
+    if !token.trim().is_empty() {
+        let host = browser.set_credential(Some(Credential::Bearer(token.trim().to_string())))?;
+        println!("Using bearer token for {}", host);
+    }
+    Ok(())
+}
+
+fn proxy(browser: &mut Browser, setting: &str) -> Result<(), Box<dyn Error>> {
+    if setting.is_empty() {
+        browser.show_proxy();
+        return Ok(());
+    }
+    if setting == "off" {
+        browser.options.proxies = Proxies::default();
+        browser.client = browser.options.build();
+        println!("Proxy disabled.");
+        return Ok(());
+    }
+    let mut args = setting.split_whitespace();
+    let (scheme, url) = match (args.next(), args.next()) {
+        (Some(scheme), Some(url)) => (scheme, url),
+        (Some(url), None) => ("all", url),
+        _ => ("all", ""),
+    };
+    let proxy = if url == "off" { None } else { Some(url) };
+    browser.set_proxy(scheme, proxy)?;
+    match proxy {
+        Some(url) => println!("Proxy for {} set to {}", scheme, url),
+        None => println!("Proxy for {} disabled.", scheme),
+    }
+    Ok(())
+}
+
+fn profile(browser: &mut Browser, name: &str) -> Result<(), Box<dyn Error>> {
+    if name.is_empty() {
+        println!(
+            "Profile: {}",
+            browser.profile.as_deref().unwrap_or("default")
+        );
+        return Ok(());
+    }
+    let profile = if name == "default" { None } else { Some(name) };
+    browser.switch_profile(profile)?;
+    println!("Switched to profile {}", name);
+    Ok(())
+}
+
+fn incognito(browser: &mut Browser, _: &str) -> Result<(), Box<dyn Error>> {
+    browser.set_private(!browser.private);
+    browser.display_page()?;
+    if browser.private {
+        println!("Private browsing is on. History, cache and cookies are not recorded.");
+    } else {
+        println!("Private browsing is off.");
+    }
+    Ok(())
+}
+
+impl Command {
+    const fn with_aliases(self, aliases: &'static [&'static str]) -> Command {
+        Command { aliases, ..self }
+    }
+
+    pub fn usage(&self) -> String {
+        if self.args.is_empty() {
+            self.name.to_string()
+        } else {
+            format!("{} {}", self.name, self.args)
+        }
+    }
+
+    fn score(&self, query: &str) -> Option<usize> {
+        let name = self.name.to_lowercase();
+        let description = self.description.to_lowercase();
+        if name.starts_with(query) {
+            Some(0)
+        } else if name.contains(query) {
+            Some(1)
+        } else if is_subsequence(query, &name) {
+            Some(2)
+        } else if description.contains(query) {
+            Some(3)
+        } else if is_subsequence(query, &description) {
+            Some(4)
+        } else {
+            None
+        }
+    }
+}
+
+fn is_subsequence(query: &str, text: &str) -> bool {
+    let mut chars = text.chars();
+    query.chars().all(|c| chars.any(|t| t == c))
+}
+
+fn matching(query: &str) -> Vec<&'static Command> {
+    let query = query.to_lowercase();
+    let mut matches: Vec<(usize, &Command)> = COMMANDS
+        .iter()
+        .filter_map(|command| Some((command.score(&query)?, command)))
+        .collect();
+    matches.sort_by_key(|(score, _)| *score);
+    matches.into_iter().map(|(_, command)| command).collect()
+}
+
+pub fn palette() -> io::Result<Option<&'static Command>> {
+    if !io::stdin().is_terminal() {
+        println!("The command palette needs an interactive terminal.");
+        return Ok(None);
+    }
+    enable_raw_mode()?;
+    let picked = run_palette();
+    disable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        ResetColor,
+        Clear(ClearType::All),
+        cursor::MoveTo(0, 0)
+    )?;
+    picked
+}
+
+fn run_palette() -> io::Result<Option<&'static Command>> {
+    let mut query = String::new();
+    let mut selected = 0;
+
+    loop {
+        let matches = matching(&query);
+        let (width, height) = terminal::size()?;
+        let rows = (height as usize).saturating_sub(4).max(1);
+        selected = selected.min(matches.len().saturating_sub(1));
+        let first = selected.saturating_sub(rows - 1);
+
+        execute!(
+            io::stdout(),
+            Clear(ClearType::All),
+            cursor::MoveTo(0, 0),
+            SetBackgroundColor(Color::DarkMagenta),
+            SetForegroundColor(Color::White)
+        )?;
+        print!(" Command Palette ");
+        execute!(io::stdout(), ResetColor, cursor::MoveTo(0, 1))?;
+        print!("> {}", query);
+
+        for (row, command) in matches.iter().enumerate().skip(first).take(rows) {
+            execute!(io::stdout(), cursor::MoveTo(0, (row - first + 3) as u16))?;
+            if row == selected {
+                execute!(
+                    io::stdout(),
+                    SetBackgroundColor(Color::DarkBlue),
+                    SetForegroundColor(Color::White)
+                )?;
+            } else {
+                execute!(io::stdout(), SetForegroundColor(Color::Yellow))?;
+            }
+            let line = format!(" {:<24} {}", command.usage(), command.description);
+            print!("{}", line.chars().take(width as usize).collect::<String>());
+            execute!(io::stdout(), ResetColor)?;
+        }
+        execute!(
+            io::stdout(),
+            cursor::MoveTo((query.chars().count() + 2) as u16, 1)
+        )?;
+        io::stdout().flush()?;
+
+        let key = match read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+        match key.code {
+            KeyCode::Esc => return Ok(None),
+            KeyCode::Enter => return Ok(matches.get(selected).copied()),
+            KeyCode::Up => selected = selected.saturating_sub(1),
+            KeyCode::Down => selected += 1,
+            KeyCode::Backspace => {
+                query.pop();
+                selected = 0;
+            }
+            KeyCode::Char(c) => {
+                query.push(c);
+                selected = 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn help() -> io::Result<()> {
+    if !io::stdin().is_terminal() {
+        print_help(COMMANDS.iter());
+        println!("\n{}", HELP_NOTES.join("\n"));
+        return Ok(());
+    }
+
+    let mut filter = String::new();
+    let mut page = 0;
+    loop {
+        let shown: Vec<&Command> = if filter.is_empty() {
+            COMMANDS.iter().collect()
+        } else {
+            COMMANDS
+                .iter()
+                .filter(|command| command.score(&filter).is_some())
+                .collect()
+        };
+        let (_, height) = terminal::size()?;
+        let per_page = (height as usize).saturating_sub(HELP_CHROME_ROWS).max(1);
+        let pages = shown.len().div_ceil(per_page).max(1);
+        page = page.min(pages - 1);
+
+        execute!(io::stdout(), Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+        execute!(
+            io::stdout(),
+            SetBackgroundColor(Color::DarkBlue),
+            SetForegroundColor(Color::White)
+        )?;
+        println!(" Help ({}/{}) ", page + 1, pages);
+        execute!(io::stdout(), ResetColor)?;
+        println!();
+        if shown.is_empty() {
+            println!("No commands match '{}'.", filter);
+        }
+        print_help(shown.iter().skip(page * per_page).take(per_page).copied());
+
+        println!("\n{}", HELP_NOTES.join("\n"));
+        println!("\nCommands:");
+        println!("n - Next page    p - Previous page");
+        println!("/TEXT - Show commands matching TEXT (/ alone shows all)");
+        println!("q - Return to browser");
+
+        print!("\nEnter command: ");
+        io::stdout().flush()?;
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        let input = input.trim();
+
+        if input == "q" || (input.is_empty() && page + 1 == pages) {
+            break;
+        } else if input == "n" || input.is_empty() {
+            page = (page + 1).min(pages - 1);
+        } else if input == "p" {
+            page = page.saturating_sub(1);
+        } else if let Some(text) = input.strip_prefix('/') {
+            filter = text.trim().to_lowercase();
+            page = 0;
+        }
+    }
+    Ok(())
+}
+
+fn print_help<'a>(commands: impl Iterator<Item = &'a Command>) {
+    for command in commands {
+        execute!(io::stdout(), SetForegroundColor(Color::Yellow)).ok();
+        print!(" {:<24}", command.usage());
+        execute!(io::stdout(), ResetColor).ok();
+        println!(" {}", command.description);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_splits_verb_and_argument() {
+        assert_eq!(parse("g  example.com "), ("g", "example.com"));
+        assert_eq!(parse("proxy http off"), ("proxy", "http off"));
+        assert_eq!(parse("'a"), ("'", "a"));
+        assert_eq!(parse(":12"), ("line", "12"));
+        assert_eq!(parse("tn"), ("tn", ""));
+    }
+
+    #[test]
+    fn find_matches_names_and_aliases() {
+        assert_eq!(find("h").map(|command| command.name), Some("help"));
+        assert_eq!(
+            find("search-all").map(|command| command.name),
+            Some("search-all")
+        );
+        assert!(find("nonsense").is_none());
+    }
+}
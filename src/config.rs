@@ -0,0 +1,142 @@
+use crate::keys::Action;
+use crossterm::style::Color;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+
+pub const CONFIG_FILE: &str = "config.toml";
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+pub const DEFAULT_SCROLL_STEP: usize = 5;
+pub const DEFAULT_MAX_HISTORY: usize = 50;
+pub const DEFAULT_RETRIES: u32 = 3;
+pub const DEFAULT_LARGE_PAGE_BYTES: u64 = 2 * 1024 * 1024;
+pub const DEFAULT_MAX_BODY_BYTES: u64 = 50 * 1024 * 1024;
+pub const DEFAULT_PREFETCH_CONCURRENCY: usize = 2;
+pub const DEFAULT_THEME: &str = "dark";
+pub const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36";
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub home_url: Option<String>,
+    pub render_width: Option<usize>,
+    pub timeout_secs: u64,
+    pub scroll_step: usize,
+    pub smooth_scroll: bool,
+    pub max_history: usize,
+    pub retries: u32,
+    pub user_agent: String,
+    pub keymap: String,
+    pub keys: HashMap<String, Action>,
+    pub proxy: Proxies,
+    pub doh_url: Option<String>,
+    pub blocklist: Option<String>,
+    pub accept_invalid_certs: bool,
+    pub https_only: bool,
+    pub respect_robots: bool,
+    pub host_delay_ms: u64,
+    pub max_downloads: usize,
+    pub prefetch: bool,
+    pub prefetch_concurrency: usize,
+    pub mouse: bool,
+    pub line_numbers: bool,
+    pub queue_auto_remove: bool,
+    pub large_page_bytes: u64,
+    pub max_body_bytes: u64,
+    pub bangs: HashMap<String, String>,
+    pub theme: String,
+    pub colors: Colors,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Proxies {
+    pub all: Option<String>,
+    pub http: Option<String>,
+    pub https: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Colors {
+    pub header_bg: Option<Color>,
+    pub header_fg: Option<Color>,
+    pub url: Option<Color>,
+    pub heading: Option<Color>,
+    pub link: Option<Color>,
+    pub text: Option<Color>,
+    pub status_bg: Option<Color>,
+    pub status_fg: Option<Color>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            home_url: None,
+            render_width: None,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            scroll_step: DEFAULT_SCROLL_STEP,
+            smooth_scroll: false,
+            max_history: DEFAULT_MAX_HISTORY,
+            retries: DEFAULT_RETRIES,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            keymap: "default".to_string(),
+            keys: HashMap::new(),
+            proxy: Proxies::default(),
+            doh_url: None,
+            blocklist: None,
+            accept_invalid_certs: false,
+            https_only: false,
+            respect_robots: false,
+            host_delay_ms: 0,
+            max_downloads: 0,
+            prefetch: false,
+            prefetch_concurrency: DEFAULT_PREFETCH_CONCURRENCY,
+            mouse: true,
+            line_numbers: true,
+            queue_auto_remove: true,
+            large_page_bytes: DEFAULT_LARGE_PAGE_BYTES,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            bangs: HashMap::new(),
+            theme: DEFAULT_THEME.to_string(),
+            colors: Colors::default(),
+        }
+    }
+}
+
+impl Proxies {
+    pub fn to_reqwest(&self) -> reqwest::Result<Vec<reqwest::Proxy>> {
+        let mut proxies = Vec::new();
+        if let Some(url) = &self.http {
+            proxies.push(reqwest::Proxy::http(url)?);
+        }
+        if let Some(url) = &self.https {
+            proxies.push(reqwest::Proxy::https(url)?);
+        }
+        if let Some(url) = &self.all {
+            proxies.push(reqwest::Proxy::all(url)?);
+        }
+        Ok(proxies)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.all.is_none() && self.http.is_none() && self.https.is_none()
+    }
+}
+
+impl Config {
+    pub fn load() -> Self {
+        let text = match fs::read_to_string(CONFIG_FILE) {
+            Ok(text) => text,
+            Err(_) => return Config::default(),
+        };
+
+        match toml::from_str(&text) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Ignoring {}: {}", CONFIG_FILE, e);
+                Config::default()
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use reqwest::blocking::Request;
+use reqwest::header::{HeaderMap, IF_MODIFIED_SINCE, IF_NONE_MATCH};
+use reqwest::Method;
+
+const SKIPPED_HEADERS: &[reqwest::header::HeaderName] = &[IF_NONE_MATCH, IF_MODIFIED_SINCE];
+
+pub struct SentRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: HeaderMap,
+    pub body: Option<Vec<u8>>,
+}
+
+impl SentRequest {
+    pub fn record(request: &Request) -> Self {
+        SentRequest {
+            method: request.method().clone(),
+            url: request.url().to_string(),
+            headers: request.headers().clone(),
+            body: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(<[u8]>::to_vec),
+        }
+    }
+
+    pub fn to_command(&self, options: &[String]) -> String {
+        let mut args = vec!["curl".to_string()];
+        args.extend(options.iter().cloned());
+        if self.method != Method::GET {
+            args.push(format!("-X {}", self.method));
+        }
+        for (name, value) in &self.headers {
+            if SKIPPED_HEADERS.contains(name) {
+                continue;
+            }
+            let value = String::from_utf8_lossy(value.as_bytes());
+            args.push(format!("-H {}", quote(&format!("{}: {}", name, value))));
+        }
+        if let Some(body) = &self.body {
+            args.push(format!(
+                "--data-raw {}",
+                quote(&String::from_utf8_lossy(body))
+            ));
+        }
+        args.push(quote(&self.url));
+        args.join(" \\\n  ")
+    }
+}
+
+pub fn quote(arg: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "_-.,:/@%+=".contains(c);
+    if !arg.is_empty() && arg.chars().all(safe) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shell_special_characters_are_quoted() {
+        assert_eq!(quote("https://example.com/a-b"), "https://example.com/a-b");
+        assert_eq!(
+            quote("https://example.com/?q=1&x=2"),
+            "'https://example.com/?q=1&x=2'"
+        );
+        assert_eq!(quote("it's $HOME"), r"'it'\''s $HOME'");
+        assert_eq!(quote(""), "''");
+    }
+}
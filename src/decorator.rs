@@ -0,0 +1,195 @@
+use html2text::render::text_renderer::{TaggedLine, TextDecorator};
+use std::cell::RefCell;
+use std::rc::Rc;
+use url::Url;
+
+#[derive(Clone, Debug, Default)]
+pub struct LinkDecorator {
+    links: Rc<RefCell<Vec<String>>>,
+}
+
+impl LinkDecorator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn links(&self) -> Rc<RefCell<Vec<String>>> {
+        Rc::clone(&self.links)
+    }
+}
+
+impl TextDecorator for LinkDecorator {
+    type Annotation = ();
+
+    fn decorate_link_start(&mut self, url: &str) -> (String, Self::Annotation) {
+        self.links.borrow_mut().push(url.to_string());
+        (String::new(), ())
+    }
+
+    fn decorate_link_end(&mut self) -> String {
+        format!("[{}]", self.links.borrow().len())
+    }
+
+    fn decorate_em_start(&mut self) -> (String, Self::Annotation) {
+        ("*".to_string(), ())
+    }
+
+    fn decorate_em_end(&mut self) -> String {
+        "*".to_string()
+    }
+
+    fn decorate_strong_start(&mut self) -> (String, Self::Annotation) {
+        ("**".to_string(), ())
+    }
+
+    fn decorate_strong_end(&mut self) -> String {
+        "**".to_string()
+    }
+
+    fn decorate_strikeout_start(&mut self) -> (String, Self::Annotation) {
+        (String::new(), ())
+    }
+
+    fn decorate_strikeout_end(&mut self) -> String {
+        String::new()
+    }
+
+    fn decorate_code_start(&mut self) -> (String, Self::Annotation) {
+        ("`".to_string(), ())
+    }
+
+    fn decorate_code_end(&mut self) -> String {
+        "`".to_string()
+    }
+
+    fn decorate_preformat_first(&mut self) -> Self::Annotation {}
+
+    fn decorate_preformat_cont(&mut self) -> Self::Annotation {}
+
+    fn decorate_image(&mut self, title: &str) -> (String, Self::Annotation) {
+        (format!("[{}]", title), ())
+    }
+
+    fn header_prefix(&mut self, level: usize) -> String {
+        "#".repeat(level) + " "
+    }
+
+    fn quote_prefix(&mut self) -> String {
+        "> ".to_string()
+    }
+
+    fn unordered_item_prefix(&mut self) -> String {
+        "* ".to_string()
+    }
+
+    fn ordered_item_prefix(&mut self, i: i64) -> String {
+        format!("{}. ", i)
+    }
+
+    fn make_subblock_decorator(&self) -> Self {
+        self.clone()
+    }
+
+    fn finalise(self) -> Vec<TaggedLine<Self::Annotation>> {
+        Vec::new()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MarkdownDecorator {
+    base: Option<Url>,
+    open_links: Vec<String>,
+}
+
+impl MarkdownDecorator {
+    pub fn new(base: Option<Url>) -> Self {
+        MarkdownDecorator {
+            base,
+            open_links: Vec::new(),
+        }
+    }
+}
+
+impl TextDecorator for MarkdownDecorator {
+    type Annotation = ();
+
+    fn decorate_link_start(&mut self, url: &str) -> (String, Self::Annotation) {
+        let url = match &self.base {
+            Some(base) => base
+                .join(url)
+                .map(String::from)
+                .unwrap_or_else(|_| url.to_string()),
+            None => url.to_string(),
+        };
+        self.open_links.push(url);
+        ("[".to_string(), ())
+    }
+
+    fn decorate_link_end(&mut self) -> String {
+        format!("]({})", self.open_links.pop().unwrap_or_default())
+    }
+
+    fn decorate_em_start(&mut self) -> (String, Self::Annotation) {
+        ("*".to_string(), ())
+    }
+
+    fn decorate_em_end(&mut self) -> String {
+        "*".to_string()
+    }
+
+    fn decorate_strong_start(&mut self) -> (String, Self::Annotation) {
+        ("**".to_string(), ())
+    }
+
+    fn decorate_strong_end(&mut self) -> String {
+        "**".to_string()
+    }
+
+    fn decorate_strikeout_start(&mut self) -> (String, Self::Annotation) {
+        ("~~".to_string(), ())
+    }
+
+    fn decorate_strikeout_end(&mut self) -> String {
+        "~~".to_string()
+    }
+
+    fn decorate_code_start(&mut self) -> (String, Self::Annotation) {
+        ("`".to_string(), ())
+    }
+
+    fn decorate_code_end(&mut self) -> String {
+        "`".to_string()
+    }
+
+    fn decorate_preformat_first(&mut self) -> Self::Annotation {}
+
+    fn decorate_preformat_cont(&mut self) -> Self::Annotation {}
+
+    fn decorate_image(&mut self, title: &str) -> (String, Self::Annotation) {
+        (format!("[{}]", title), ())
+    }
+
+    fn header_prefix(&mut self, level: usize) -> String {
+        "#".repeat(level) + " "
+    }
+
+    fn quote_prefix(&mut self) -> String {
+        "> ".to_string()
+    }
+
+    fn unordered_item_prefix(&mut self) -> String {
+        "* ".to_string()
+    }
+
+    fn ordered_item_prefix(&mut self, i: i64) -> String {
+        format!("{}. ", i)
+    }
+
+    fn make_subblock_decorator(&self) -> Self {
+        self.clone()
+    }
+
+    fn finalise(self) -> Vec<TaggedLine<Self::Annotation>> {
+        Vec::new()
+    }
+}
@@ -0,0 +1,75 @@
+use reqwest::blocking::Client;
+use reqwest::header::ACCEPT;
+use serde::Deserialize;
+use std::error::Error;
+use std::net::IpAddr;
+use std::time::Duration;
+
+const RECORD_TYPES: &[&str] = &["A", "AAAA"];
+const MIN_TTL: u64 = 10;
+
+#[derive(Deserialize)]
+struct Response {
+    #[serde(rename = "Answer", default)]
+    answer: Vec<Record>,
+}
+
+#[derive(Deserialize)]
+struct Record {
+    data: String,
+    #[serde(rename = "TTL", default)]
+    ttl: u64,
+}
+
+pub fn lookup(
+    client: &Client,
+    endpoint: &str,
+    host: &str,
+) -> Result<(Vec<IpAddr>, Duration), Box<dyn Error>> {
+    let mut addresses = Vec::new();
+    let mut ttl: Option<u64> = None;
+    for record_type in RECORD_TYPES {
+        let body = client
+            .get(endpoint)
+            .query(&[("name", host), ("type", record_type)])
+            .header(ACCEPT, "application/dns-json")
+            .send()?
+            .error_for_status()?
+            .text()?;
+        for (address, record_ttl) in parse(&body)? {
+            addresses.push(address);
+            ttl = Some(ttl.map_or(record_ttl, |ttl| ttl.min(record_ttl)));
+        }
+    }
+    if addresses.is_empty() {
+        return Err(format!("{} returned no addresses for {}", endpoint, host).into());
+    }
+    let ttl = ttl.unwrap_or_default().max(MIN_TTL);
+    Ok((addresses, Duration::from_secs(ttl)))
+}
+
+fn parse(body: &str) -> serde_json::Result<Vec<(IpAddr, u64)>> {
+    let response: Response = serde_json::from_str(body)?;
+    Ok(response
+        .answer
+        .iter()
+        .filter_map(|record| Some((record.data.parse().ok()?, record.ttl)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cname_records_are_skipped() {
+        let body = r#"{"Status":0,"Answer":[
+            {"name":"www.example.com","type":5,"data":"example.com."},
+            {"name":"example.com","type":1,"TTL":300,"data":"93.184.215.14"}]}"#;
+        assert_eq!(
+            parse(body).unwrap(),
+            vec![("93.184.215.14".parse::<IpAddr>().unwrap(), 300)]
+        );
+        assert!(parse(r#"{"Status":3}"#).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,123 @@
+use html5ever::serialize::{serialize, SerializeOpts, TraversalScope};
+use html5ever::tendril::TendrilSink;
+use html5ever::{parse_document, ParseOpts};
+use markup5ever_rcdom::{Handle, NodeData, RcDom, SerializableHandle};
+use std::rc::Rc;
+
+pub fn parse(html: &str) -> RcDom {
+    parse_document(RcDom::default(), ParseOpts::default())
+        .from_utf8()
+        .one(html.as_bytes())
+}
+
+pub fn tag_name(node: &Handle) -> Option<String> {
+    match &node.data {
+        NodeData::Element { name, .. } => Some(name.local.as_ref().to_ascii_lowercase()),
+        _ => None,
+    }
+}
+
+pub fn attr(node: &Handle, name: &str) -> Option<String> {
+    match &node.data {
+        NodeData::Element { attrs, .. } => attrs
+            .borrow()
+            .iter()
+            .find(|attr| attr.name.local.as_ref().eq_ignore_ascii_case(name))
+            .map(|attr| attr.value.to_string()),
+        _ => None,
+    }
+}
+
+pub fn text_of(node: &Handle) -> String {
+    let mut text = String::new();
+    collect_text(node, &mut text);
+    text.trim().to_string()
+}
+
+fn collect_text(node: &Handle, text: &mut String) {
+    for child in node.children.borrow().iter() {
+        match &child.data {
+            NodeData::Text { contents } => text.push_str(&contents.borrow()),
+            NodeData::Element { .. } => collect_text(child, text),
+            _ => {}
+        }
+    }
+}
+
+pub fn anchor_text(html: &str, fragment: &str) -> Option<String> {
+    let dom = parse(html);
+    let anchor = find_anchor(&dom.document, fragment)?;
+    let text = text_of(&anchor);
+    if !text.is_empty() {
+        return Some(text);
+    }
+
+    let parent = anchor.parent.take();
+    anchor.parent.set(parent.clone());
+    let parent = parent?.upgrade()?;
+    let siblings = parent.children.borrow();
+    let position = siblings
+        .iter()
+        .position(|sibling| Rc::ptr_eq(sibling, &anchor))?;
+    siblings[position + 1..]
+        .iter()
+        .map(|sibling| match &sibling.data {
+            NodeData::Text { contents } => contents.borrow().trim().to_string(),
+            _ => text_of(sibling),
+        })
+        .find(|text| !text.is_empty())
+}
+
+fn find_anchor(node: &Handle, fragment: &str) -> Option<Handle> {
+    for child in node.children.borrow().iter() {
+        let named = tag_name(child).as_deref() == Some("a")
+            && attr(child, "name").as_deref() == Some(fragment);
+        if named || attr(child, "id").as_deref() == Some(fragment) {
+            return Some(child.clone());
+        }
+        if let Some(found) = find_anchor(child, fragment) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+pub fn find_all(node: &Handle, tag: &str) -> Vec<Handle> {
+    let mut found = Vec::new();
+    collect_elements(node, tag, &mut found);
+    found
+}
+
+fn collect_elements(node: &Handle, tag: &str, found: &mut Vec<Handle>) {
+    for child in node.children.borrow().iter() {
+        if tag_name(child).as_deref() == Some(tag) {
+            found.push(child.clone());
+        }
+        collect_elements(child, tag, found);
+    }
+}
+
+pub fn to_html(node: &Handle) -> String {
+    let mut html = Vec::new();
+    let opts = SerializeOpts {
+        traversal_scope: TraversalScope::IncludeNode,
+        ..Default::default()
+    };
+    if serialize(&mut html, &SerializableHandle::from(node.clone()), opts).is_err() {
+        return String::new();
+    }
+    String::from_utf8_lossy(&html).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anchor_text_falls_back_to_following_content() {
+        let html = r#"<h2 id="intro">Intro <b>text</b></h2><p><a name="end"></a> The end</p>"#;
+        assert_eq!(anchor_text(html, "intro").as_deref(), Some("Intro text"));
+        assert_eq!(anchor_text(html, "end").as_deref(), Some("The end"));
+        assert_eq!(anchor_text(html, "missing"), None);
+    }
+}
@@ -0,0 +1,233 @@
+use reqwest::blocking::{RequestBuilder, Response};
+use reqwest::header::{HeaderMap, CONTENT_RANGE, ETAG, IF_RANGE, LAST_MODIFIED, LOCATION, RANGE};
+use reqwest::StatusCode;
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const VALIDATOR_SUFFIX: &str = ".resume";
+
+#[derive(Clone)]
+pub enum State {
+    Active,
+    Finished,
+    Failed(String),
+}
+
+struct Progress {
+    downloaded: AtomicU64,
+    total: AtomicU64,
+    state: Mutex<State>,
+}
+
+pub struct Download {
+    pub url: String,
+    pub filename: String,
+    progress: Arc<Progress>,
+}
+
+impl Download {
+    pub fn start(request: RequestBuilder, url: &str, filename: &str) -> Self {
+        let progress = Arc::new(Progress {
+            downloaded: AtomicU64::new(0),
+            total: AtomicU64::new(0),
+            state: Mutex::new(State::Active),
+        });
+
+        thread::spawn({
+            let progress = progress.clone();
+            let filename = filename.to_string();
+            move || {
+                let state = match fetch(request, &filename, &progress) {
+                    Ok(()) => State::Finished,
+                    Err(e) => State::Failed(e.to_string()),
+                };
+                if let Ok(mut current) = progress.state.lock() {
+                    *current = state;
+                }
+            }
+        });
+
+        Download {
+            url: url.to_string(),
+            filename: filename.to_string(),
+            progress,
+        }
+    }
+
+    pub fn downloaded(&self) -> u64 {
+        self.progress.downloaded.load(Ordering::Relaxed)
+    }
+
+    pub fn total(&self) -> Option<u64> {
+        match self.progress.total.load(Ordering::Relaxed) {
+            0 => None,
+            total => Some(total),
+        }
+    }
+
+    pub fn state(&self) -> State {
+        self.progress
+            .state
+            .lock()
+            .map(|state| state.clone())
+            .unwrap_or_else(|_| State::Failed("download thread panicked".to_string()))
+    }
+}
+
+pub fn can_resume(filename: &str) -> bool {
+    partial_size(filename) > 0 && fs::metadata(validator_file(filename)).is_ok()
+}
+
+fn validator_file(filename: &str) -> String {
+    format!("{}{}", filename, VALIDATOR_SUFFIX)
+}
+
+fn partial_size(filename: &str) -> u64 {
+    fs::metadata(filename)
+        .map(|metadata| metadata.len())
+        .unwrap_or(0)
+}
+
+fn fetch(
+    request: RequestBuilder,
+    filename: &str,
+    progress: &Progress,
+) -> Result<(), Box<dyn Error>> {
+    let sidecar = validator_file(filename);
+    let validator = fs::read_to_string(&sidecar).ok();
+    let existing = match &validator {
+        Some(_) => partial_size(filename),
+        None => 0,
+    };
+    let full = request.try_clone();
+    let mut response = match validator.as_deref().filter(|_| existing > 0) {
+        Some(validator) => request
+            .header(RANGE, format!("bytes={}-", existing))
+            .header(IF_RANGE, validator.trim())
+            .send()?,
+        None => request.send()?,
+    };
+
+    if response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+        if existing > 0 && content_range(&response).is_some_and(|(_, total)| total == existing) {
+            progress.total.store(existing, Ordering::Relaxed);
+            progress.downloaded.store(existing, Ordering::Relaxed);
+            let _ = fs::remove_file(&sidecar);
+            return Ok(());
+        }
+        response = full
+            .ok_or("cannot repeat the request without a range")?
+            .send()?;
+    }
+    let response = response.error_for_status()?;
+    if response.status().is_redirection() {
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("another address");
+        return Err(format!("redirected to {}; open it and download again", location).into());
+    }
+
+    let resumed = existing > 0
+        && response.status() == StatusCode::PARTIAL_CONTENT
+        && content_range(&response).is_some_and(|(start, _)| start == existing);
+    match resume_validator(response.headers()) {
+        Some(validator) => fs::write(&sidecar, validator)?,
+        None => {
+            let _ = fs::remove_file(&sidecar);
+        }
+    }
+    let mut file = if resumed {
+        progress.downloaded.store(existing, Ordering::Relaxed);
+        OpenOptions::new().append(true).open(filename)?
+    } else {
+        File::create(filename)?
+    };
+    let offset = if resumed { existing } else { 0 };
+    if let Some(length) = response.content_length() {
+        progress.total.store(offset + length, Ordering::Relaxed);
+    }
+    copy(response, &mut file, progress)?;
+    let _ = fs::remove_file(&sidecar);
+    Ok(())
+}
+
+fn resume_validator(headers: &HeaderMap) -> Option<String> {
+    let strong_etag = headers
+        .get(ETAG)
+        .and_then(|value| value.to_str().ok())
+        .filter(|etag| !etag.starts_with("W/"));
+    strong_etag
+        .or_else(|| headers.get(LAST_MODIFIED)?.to_str().ok())
+        .map(String::from)
+}
+
+fn copy(
+    mut response: Response,
+    file: &mut File,
+    progress: &Progress,
+) -> Result<(), Box<dyn Error>> {
+    let mut buffer = [0; 8192];
+    loop {
+        let read = response.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        file.write_all(&buffer[..read])?;
+        progress
+            .downloaded
+            .fetch_add(read as u64, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+fn content_range(response: &Response) -> Option<(u64, u64)> {
+    let value = response.headers().get(CONTENT_RANGE)?.to_str().ok()?;
+    parse_content_range(value)
+}
+
+fn parse_content_range(value: &str) -> Option<(u64, u64)> {
+    let (range, total) = value.strip_prefix("bytes ")?.split_once('/')?;
+    let total = total.trim().parse().ok()?;
+    let start = match range.split_once('-') {
+        Some((start, _)) => start.trim().parse().ok()?,
+        None if range == "*" => total,
+        None => return None,
+    };
+    Some((start, total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_range_gives_start_and_total() {
+        assert_eq!(parse_content_range("bytes 100-199/200"), Some((100, 200)));
+        assert_eq!(parse_content_range("bytes */200"), Some((200, 200)));
+        assert_eq!(parse_content_range("bytes 0-9/*"), None);
+        assert_eq!(parse_content_range("items 0-9/10"), None);
+    }
+
+    #[test]
+    fn weak_etags_are_not_used_to_resume() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            LAST_MODIFIED,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        headers.insert(ETAG, "W/\"abc\"".parse().unwrap());
+        assert_eq!(
+            resume_validator(&headers).as_deref(),
+            Some("Wed, 21 Oct 2015 07:28:00 GMT")
+        );
+        headers.insert(ETAG, "\"abc\"".parse().unwrap());
+        assert_eq!(resume_validator(&headers).as_deref(), Some("\"abc\""));
+        assert_eq!(resume_validator(&HeaderMap::new()), None);
+    }
+}
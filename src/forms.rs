@@ -0,0 +1,93 @@
+use crate::dom;
+use markup5ever_rcdom::Handle;
+
+const SKIPPED_INPUTS: &[&str] = &["submit", "button", "image", "reset", "file"];
+
+pub struct Form {
+    pub action: String,
+    pub method: String,
+    pub fields: Vec<Field>,
+}
+
+pub struct Field {
+    pub name: String,
+    pub kind: String,
+    pub value: String,
+}
+
+impl Field {
+    pub fn is_editable(&self) -> bool {
+        self.kind != "hidden"
+    }
+}
+
+pub fn parse(html: &str) -> Vec<Form> {
+    let dom = dom::parse(html);
+    dom::find_all(&dom.document, "form")
+        .iter()
+        .map(|form| Form {
+            action: dom::attr(form, "action").unwrap_or_default(),
+            method: dom::attr(form, "method")
+                .unwrap_or_else(|| "get".to_string())
+                .to_uppercase(),
+            fields: fields_of(form),
+        })
+        .collect()
+}
+
+fn fields_of(form: &Handle) -> Vec<Field> {
+    let mut fields = Vec::new();
+    collect_fields(form, &mut fields);
+    fields
+}
+
+fn collect_fields(node: &Handle, fields: &mut Vec<Field>) {
+    for child in node.children.borrow().iter() {
+        let tag = dom::tag_name(child);
+        let name = dom::attr(child, "name").filter(|name| !name.is_empty());
+        match (tag.as_deref(), name) {
+            (Some("input"), Some(name)) => {
+                let kind = dom::attr(child, "type")
+                    .unwrap_or_else(|| "text".to_string())
+                    .to_lowercase();
+                let checkable = kind == "checkbox" || kind == "radio";
+                if SKIPPED_INPUTS.contains(&kind.as_str())
+                    || (checkable && dom::attr(child, "checked").is_none())
+                {
+                    continue;
+                }
+                let value = dom::attr(child, "value").unwrap_or_else(|| {
+                    if checkable {
+                        "on".to_string()
+                    } else {
+                        String::new()
+                    }
+                });
+                fields.push(Field { name, kind, value });
+            }
+            (Some("textarea"), Some(name)) => fields.push(Field {
+                name,
+                kind: "textarea".to_string(),
+                value: dom::text_of(child),
+            }),
+            (Some("select"), Some(name)) => {
+                let options = dom::find_all(child, "option");
+                let selected = options
+                    .iter()
+                    .find(|option| dom::attr(option, "selected").is_some())
+                    .or(options.first());
+                let value = selected
+                    .map(|option| {
+                        dom::attr(option, "value").unwrap_or_else(|| dom::text_of(option))
+                    })
+                    .unwrap_or_default();
+                fields.push(Field {
+                    name,
+                    kind: "select".to_string(),
+                    value,
+                });
+            }
+            _ => collect_fields(child, fields),
+        }
+    }
+}
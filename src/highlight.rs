@@ -0,0 +1,169 @@
+use crossterm::style::Color;
+
+const TEXT: Color = Color::White;
+const PUNCTUATION: Color = Color::DarkGrey;
+const TAG: Color = Color::Blue;
+const ATTRIBUTE: Color = Color::Cyan;
+const STRING: Color = Color::Green;
+const KEY: Color = Color::Yellow;
+const NUMBER: Color = Color::Cyan;
+const LITERAL: Color = Color::Magenta;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Language {
+    Markup,
+    Json,
+    Plain,
+}
+
+impl Language {
+    pub fn from_content_type(content_type: &str) -> Self {
+        if content_type.contains("json") {
+            Language::Json
+        } else if content_type.contains("html") || content_type.contains("xml") {
+            Language::Markup
+        } else {
+            Language::Plain
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    Text,
+    TagName,
+    Attributes,
+    Quoted(char),
+    Comment,
+}
+
+pub struct Highlighter {
+    language: Language,
+    state: State,
+}
+
+impl Highlighter {
+    pub fn new(language: Language) -> Self {
+        Highlighter {
+            language,
+            state: State::Text,
+        }
+    }
+
+    pub fn highlight(&mut self, line: &str) -> Vec<(Color, String)> {
+        let mut spans = Vec::new();
+        match self.language {
+            Language::Markup => self.markup(line, &mut spans),
+            Language::Json => json(line, &mut spans),
+            Language::Plain => push(&mut spans, TEXT, line),
+        }
+        spans
+    }
+
+    fn markup(&mut self, line: &str, spans: &mut Vec<(Color, String)>) {
+        let mut rest = line;
+
+        while let Some(c) = rest.chars().next() {
+            let mut len = c.len_utf8();
+            let color = match self.state {
+                State::Text if rest.starts_with("<!--") => {
+                    self.state = State::Comment;
+                    len = 4;
+                    PUNCTUATION
+                }
+                State::Text if c == '<' => {
+                    self.state = State::TagName;
+                    PUNCTUATION
+                }
+                State::Text => TEXT,
+                State::Comment => {
+                    if rest.starts_with("-->") {
+                        self.state = State::Text;
+                        len = 3;
+                    }
+                    PUNCTUATION
+                }
+                State::TagName | State::Attributes if c == '>' => {
+                    self.state = State::Text;
+                    PUNCTUATION
+                }
+                State::TagName if c.is_whitespace() => {
+                    self.state = State::Attributes;
+                    TEXT
+                }
+                State::TagName => TAG,
+                State::Attributes if c == '"' || c == '\'' => {
+                    self.state = State::Quoted(c);
+                    STRING
+                }
+                State::Attributes if c == '=' || c == '/' => PUNCTUATION,
+                State::Attributes => ATTRIBUTE,
+                State::Quoted(quote) => {
+                    if c == quote {
+                        self.state = State::Attributes;
+                    }
+                    STRING
+                }
+            };
+            push(spans, color, &rest[..len]);
+            rest = &rest[len..];
+        }
+
+        if self.state == State::TagName {
+            self.state = State::Attributes;
+        }
+    }
+}
+
+fn json(line: &str, spans: &mut Vec<(Color, String)>) {
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '"' => {
+                let mut end = line.len();
+                let mut escaped = false;
+                for (i, c) in chars.by_ref() {
+                    if c == '"' && !escaped {
+                        end = i + 1;
+                        break;
+                    }
+                    escaped = c == '\\' && !escaped;
+                }
+                let is_key = line[end..].trim_start().starts_with(':');
+                push(spans, if is_key { KEY } else { STRING }, &line[start..end]);
+            }
+            '-' | '0'..='9' => {
+                let mut end = start + 1;
+                while let Some(&(i, c)) = chars.peek() {
+                    if !(c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-')) {
+                        break;
+                    }
+                    end = i + 1;
+                    chars.next();
+                }
+                push(spans, NUMBER, &line[start..end]);
+            }
+            c if c.is_ascii_alphabetic() => {
+                let mut end = start + 1;
+                while let Some(&(i, c)) = chars.peek() {
+                    if !c.is_ascii_alphabetic() {
+                        break;
+                    }
+                    end = i + 1;
+                    chars.next();
+                }
+                push(spans, LITERAL, &line[start..end]);
+            }
+            c if c.is_whitespace() => push(spans, TEXT, &line[start..start + c.len_utf8()]),
+            _ => push(spans, PUNCTUATION, &line[start..start + c.len_utf8()]),
+        }
+    }
+}
+
+fn push(spans: &mut Vec<(Color, String)>, color: Color, text: &str) {
+    match spans.last_mut() {
+        Some((last, span)) if *last == color => span.push_str(text),
+        _ => spans.push((color, text.to_string())),
+    }
+}
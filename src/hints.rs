@@ -0,0 +1,31 @@
+pub const HINT_CHARS: &str = "asdfghjkl";
+
+pub fn labels(count: usize) -> Vec<String> {
+    let chars: Vec<char> = HINT_CHARS.chars().collect();
+    let mut labels = vec![String::new()];
+    let mut offset = 0;
+    while labels.len() - offset < count || labels.len() == 1 {
+        let prefix = labels[offset].clone();
+        offset += 1;
+        labels.extend(chars.iter().map(|c| format!("{}{}", prefix, c)));
+    }
+    let mut labels: Vec<String> = labels.into_iter().skip(offset).take(count).collect();
+    labels.sort_by_key(|label| label.len());
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_are_short_and_prefix_free() {
+        assert_eq!(labels(3), vec!["a", "s", "d"]);
+        let many = labels(12);
+        assert_eq!(many.len(), 12);
+        assert!(many.iter().all(|label| label.len() <= 2));
+        for a in &many {
+            assert!(!many.iter().any(|b| a != b && b.starts_with(a.as_str())));
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use serde_json::Value;
+use std::collections::HashSet;
+
+pub struct JsonTree {
+    root: Value,
+    collapsed: HashSet<String>,
+}
+
+pub struct TreeLine {
+    pub path: String,
+    pub text: String,
+    pub is_container: bool,
+}
+
+impl JsonTree {
+    pub fn new(root: Value) -> Self {
+        JsonTree {
+            root,
+            collapsed: HashSet::new(),
+        }
+    }
+
+    pub fn lines(&self) -> Vec<TreeLine> {
+        let mut lines = Vec::new();
+        self.collect(&self.root, None, String::new(), 0, &mut lines);
+        lines
+    }
+
+    pub fn render(&self) -> String {
+        let lines: Vec<String> = self.lines().into_iter().map(|line| line.text).collect();
+        lines.join("\n")
+    }
+
+    pub fn pretty(&self) -> String {
+        serde_json::to_string_pretty(&self.root).unwrap_or_default()
+    }
+
+    pub fn select(&self, query: &str) -> Result<&Value, String> {
+        let mut value = &self.root;
+        let mut location = String::new();
+        for segment in segments(query)? {
+            value = match (segment, value) {
+                (Segment::Key(key), Value::Object(map)) => match map.get(&key) {
+                    Some(child) => {
+                        location.push_str(&format!(".{}", key));
+                        child
+                    }
+                    None => return Err(format!("no key '{}' at {}", key, root_label(&location))),
+                },
+                (Segment::Index(i), Value::Array(items)) => match items.get(i) {
+                    Some(child) => {
+                        location.push_str(&format!("[{}]", i));
+                        child
+                    }
+                    None => {
+                        return Err(format!(
+                            "index {} is out of range at {} ({} items)",
+                            i,
+                            root_label(&location),
+                            items.len()
+                        ))
+                    }
+                },
+                (Segment::Key(key), other) => {
+                    return Err(format!("cannot look up '{}' in {}", key, summary(other)))
+                }
+                (Segment::Index(i), other) => {
+                    return Err(format!("cannot take index {} of {}", i, summary(other)))
+                }
+            };
+        }
+        Ok(value)
+    }
+
+    pub fn toggle(&mut self, path: &str) {
+        if !self.collapsed.remove(path) {
+            self.collapsed.insert(path.to_string());
+        }
+    }
+
+    pub fn collapse(&mut self, path: &str) {
+        self.collapsed.insert(path.to_string());
+    }
+
+    pub fn expand(&mut self, path: &str) {
+        self.collapsed.remove(path);
+    }
+
+    fn collect(
+        &self,
+        value: &Value,
+        label: Option<String>,
+        path: String,
+        depth: usize,
+        lines: &mut Vec<TreeLine>,
+    ) {
+        let is_container = matches!(value, Value::Object(_) | Value::Array(_));
+        let collapsed = self.collapsed.contains(&path);
+        let marker = match (is_container, collapsed) {
+            (true, true) => "▸ ",
+            (true, false) => "▾ ",
+            (false, _) => "  ",
+        };
+        let label = label
+            .map(|label| format!("{}: ", label))
+            .unwrap_or_default();
+        lines.push(TreeLine {
+            text: format!(
+                "{}{}{}{}",
+                "  ".repeat(depth),
+                marker,
+                label,
+                summary(value)
+            ),
+            path: path.clone(),
+            is_container,
+        });
+
+        if collapsed {
+            return;
+        }
+        match value {
+            Value::Object(map) => {
+                for (key, child) in map {
+                    let child_path =
+                        format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+                    self.collect(child, Some(key.clone()), child_path, depth + 1, lines);
+                }
+            }
+            Value::Array(items) => {
+                for (i, child) in items.iter().enumerate() {
+                    let child_path = format!("{}/{}", path, i);
+                    self.collect(
+                        child,
+                        Some(format!("[{}]", i)),
+                        child_path,
+                        depth + 1,
+                        lines,
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+fn segments(query: &str) -> Result<Vec<Segment>, String> {
+    let mut segments = Vec::new();
+    let mut rest = query.trim();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('[') {
+            let (index, after) = after
+                .split_once(']')
+                .ok_or_else(|| format!("missing ']' in '{}'", query))?;
+            let index = index
+                .trim()
+                .parse()
+                .map_err(|_| format!("'{}' is not an array index", index))?;
+            segments.push(Segment::Index(index));
+            rest = after;
+        } else {
+            let after = rest.strip_prefix('.').unwrap_or(rest);
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            if end > 0 {
+                segments.push(Segment::Key(after[..end].to_string()));
+            } else if !after.is_empty() && !after.starts_with('[') {
+                return Err(format!("empty key in '{}'", query));
+            }
+            rest = &after[end..];
+        }
+    }
+    Ok(segments)
+}
+
+fn root_label(location: &str) -> &str {
+    if location.is_empty() {
+        "the root"
+    } else {
+        location
+    }
+}
+
+fn summary(value: &Value) -> String {
+    match value {
+        Value::Object(map) if map.len() == 1 => "{1 key}".to_string(),
+        Value::Object(map) => format!("{{{} keys}}", map.len()),
+        Value::Array(items) if items.len() == 1 => "[1 item]".to_string(),
+        Value::Array(items) => format!("[{} items]", items.len()),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn select_follows_keys_and_indexes() {
+        let tree = JsonTree::new(json!({"data": {"items": [{"id": 1}, {"id": 2}]}}));
+        assert_eq!(tree.select(".data.items[1].id"), Ok(&json!(2)));
+        assert_eq!(tree.select("data.items[0]"), Ok(&json!({"id": 1})));
+        assert_eq!(tree.select("."), Ok(&tree.root));
+        assert_eq!(
+            tree.select(".data.missing"),
+            Err("no key 'missing' at .data".to_string())
+        );
+        assert!(tree.select(".data.items[5]").is_err());
+        assert!(tree.select(".data..items").is_err());
+    }
+}
@@ -0,0 +1,91 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    ScrollUp,
+    ScrollDown,
+    HalfPageUp,
+    HalfPageDown,
+    Top,
+    Bottom,
+    Back,
+    Forward,
+    Reload,
+    HardReload,
+    Search,
+    NextMatch,
+    PreviousMatch,
+    Quit,
+}
+
+const DEFAULT_BINDINGS: &[(&str, Action)] = &[
+    ("w", Action::ScrollUp),
+    ("s", Action::ScrollDown),
+    ("u", Action::HalfPageUp),
+    ("d", Action::HalfPageDown),
+    ("gg", Action::Top),
+    ("G", Action::Bottom),
+    ("back", Action::Back),
+    ("forward", Action::Forward),
+    ("r", Action::Reload),
+    ("R", Action::HardReload),
+    ("search", Action::Search),
+    ("n", Action::NextMatch),
+    ("N", Action::PreviousMatch),
+    ("q", Action::Quit),
+    ("quit", Action::Quit),
+];
+
+const VIM_BINDINGS: &[(&str, Action)] = &[
+    ("k", Action::ScrollUp),
+    ("j", Action::ScrollDown),
+    ("H", Action::Back),
+    ("L", Action::Forward),
+    ("/", Action::Search),
+];
+
+pub struct Keymap {
+    bindings: HashMap<String, Action>,
+}
+
+impl Keymap {
+    pub fn new(profile: &str, overrides: &HashMap<String, Action>) -> Self {
+        let mut bindings: HashMap<String, Action> = DEFAULT_BINDINGS
+            .iter()
+            .map(|(key, action)| (key.to_string(), *action))
+            .collect();
+
+        match profile {
+            "vim" => bindings.extend(
+                VIM_BINDINGS
+                    .iter()
+                    .map(|(key, action)| (key.to_string(), *action)),
+            ),
+            "default" => {}
+            other => eprintln!("Unknown keymap '{}', using the default bindings", other),
+        }
+
+        bindings.extend(overrides.iter().map(|(key, action)| (key.clone(), *action)));
+        Keymap { bindings }
+    }
+
+    pub fn resolve<'a>(&self, input: &'a str) -> Option<(Action, &'a str)> {
+        if let Some(action) = self.bindings.get(input) {
+            return Some((*action, ""));
+        }
+
+        self.bindings
+            .iter()
+            .filter(|(_, action)| **action == Action::Search)
+            .find_map(|(key, action)| {
+                let rest = input.strip_prefix(key.as_str())?;
+                let needs_space = key.chars().last().is_some_and(char::is_alphanumeric);
+                if needs_space && !rest.starts_with(char::is_whitespace) {
+                    return None;
+                }
+                Some((*action, rest.trim()))
+            })
+    }
+}
@@ -1,51 +1,677 @@
+mod archive;
+mod blocklist;
+mod commands;
+mod config;
+mod curl;
+mod decorator;
+mod doh;
+mod dom;
+mod downloads;
+mod entities;
+mod forms;
+mod highlight;
+mod hints;
+mod json_tree;
+mod keys;
+mod netscape;
+mod omnibox;
+mod picture;
+mod prefetch;
+mod prompt;
+mod reader;
+mod robots;
+mod search;
+mod snapshot;
+mod spinner;
+mod table;
+mod theme;
+mod toast;
+mod xml;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use blocklist::Blocklist;
+use chrono::{DateTime, Local, Utc};
+use config::{Config, Proxies, CONFIG_FILE, DEFAULT_THEME};
 use crossterm::{
     cursor,
-    event::read,
+    event::{
+        poll, read, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEvent,
+        MouseEventKind,
+    },
     execute,
     style::{Color, ResetColor, SetBackgroundColor, SetForegroundColor},
     terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType},
 };
-use reqwest::blocking::{Client, Response};
+use curl::SentRequest;
+use decorator::{LinkDecorator, MarkdownDecorator};
+use downloads::{Download, State};
+use encoding_rs::{Encoding, UTF_8};
+use feed_rs::model::Feed;
+use forms::Form;
+use highlight::{Highlighter, Language};
+use html2text::render::text_renderer::TrivialDecorator;
+use json_tree::JsonTree;
+use keys::{Action, Keymap};
+use prefetch::Prefetcher;
+use prompt::{Context, LineEditor, COMMAND_HISTORY_FILE};
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::header::{
+    HeaderMap, HeaderName, HeaderValue, ACCEPT, AUTHORIZATION, CACHE_CONTROL, CONTENT_TYPE, ETAG,
+    IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, LOCATION,
+};
+use reqwest::redirect::Policy;
+use reqwest::tls::TlsInfo;
+use reqwest::{StatusCode, Version};
+use reqwest_cookie_store::{CookieStore, CookieStoreMutex};
+use search::Pattern;
 use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
+use spinner::Spinner;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fs::{File, OpenOptions};
-use std::io::{self, Write};
+use std::io::{self, BufReader, IsTerminal, Read, Write};
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use theme::Theme;
+use url::Url;
 
-const MAX_HISTORY: usize = 50;
 const BOOKMARKS_FILE: &str = "bookmarks.json";
+const HISTORY_FILE: &str = "history.json";
+const COOKIES_FILE: &str = "cookies.json";
+const VISITS_FILE: &str = "visits.json";
+const REQUEST_LOG_FILE: &str = "requests.log";
+const SESSION_FILE: &str = "session.json";
+const READ_LATER_FILE: &str = "read_later.json";
+const PROFILES_DIR: &str = "profiles";
+const PROFILE_FLAG: &str = "--profile";
+const MIN_GUTTER_DIGITS: usize = 4;
+const GUTTER_SEPARATOR: &str = " │ ";
+const CURRENT_MATCH_COLOR: Color = Color::Cyan;
+const MOUSE_SCROLL_LINES: isize = 3;
+const DEFAULT_RENDER_WIDTH: usize = 100;
+const CONNECT_TIMEOUT_SECS: u64 = 10;
+const TIMEOUT_ENV: &str = "SURFER_TIMEOUT";
+const PROXY_ENV: &str = "SURFER_PROXY";
+const INSECURE_FLAG: &str = "--insecure";
+const DUMP_FLAG: &str = "--dump";
+const LOG_REQUESTS_FLAG: &str = "--log-requests";
+const JSON_FLAG: &str = "--json";
+const DEFAULT_MAX_REDIRECTS: usize = 10;
+const MAX_CACHE_ENTRIES: usize = 20;
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+const DEAD_LINK_STATUSES: &[StatusCode] = &[StatusCode::NOT_FOUND, StatusCode::GONE];
+const VIEW_AS_TYPES: &[(&str, &str)] = &[
+    ("html", "text/html"),
+    ("json", "application/json"),
+    ("text", "text/plain"),
+    ("xml", "application/xml"),
+    ("markdown", "text/markdown"),
+];
+const SMOOTH_SCROLL_FRAMES: isize = 4;
+const SMOOTH_SCROLL_FRAME_TIME: Duration = Duration::from_millis(15);
+const MAX_SNAPSHOT_IMAGES: usize = 50;
+const MAX_SNAPSHOT_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
+const ANCHOR_MATCH_WORDS: usize = 6;
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+const TAB_LABEL_WIDTH: usize = 16;
+const TOP_VISITED: usize = 20;
+const MAX_CLOSED_PAGES: usize = 10;
+const MAX_NAVIGATION: usize = 100;
+const DEFAULT_BANGS: &[(&str, &str)] = &[
+    ("g", "https://www.google.com/search?q={query}"),
+    ("ddg", "https://duckduckgo.com/html/?q={query}"),
+    ("w", "https://en.wikipedia.org/w/index.php?search={query}"),
+];
+const NEXT_LABELS: &[&str] = &["next", "next page", "older posts"];
+const PREVIOUS_LABELS: &[&str] = &["prev", "previous", "previous page", "newer posts"];
+const UA_PRESETS: &[(&str, &str)] = &[
+    ("desktop", config::DEFAULT_USER_AGENT),
+    (
+        "mobile",
+        "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.0 Mobile/15E148 Safari/604.1",
+    ),
+    ("bot", concat!("rust-web-surfer/", env!("CARGO_PKG_VERSION"))),
+];
 
 #[derive(Debug, Serialize, Deserialize)]
 struct Bookmark {
     title: String,
     url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    folder: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key: Option<char>,
 }
 
-struct Browser {
-    client: Client,
+#[derive(Debug, Serialize, Deserialize)]
+struct HistoryEntry {
+    url: String,
+    visited: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct QueueEntry {
+    title: String,
+    url: String,
+    added: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Session {
+    tabs: Vec<SessionTab>,
+    active_tab: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SessionTab {
+    url: String,
+    scroll_position: usize,
+}
+
+#[derive(Clone, Copy)]
+enum BookmarkSort {
+    Added,
+    Title,
+    Url,
+}
+
+impl BookmarkSort {
+    fn next(self) -> Self {
+        match self {
+            BookmarkSort::Added => BookmarkSort::Title,
+            BookmarkSort::Title => BookmarkSort::Url,
+            BookmarkSort::Url => BookmarkSort::Added,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            BookmarkSort::Added => "date added",
+            BookmarkSort::Title => "title",
+            BookmarkSort::Url => "URL",
+        }
+    }
+}
+
+#[derive(Clone)]
+struct ClientOptions {
+    timeout: Duration,
+    max_redirects: usize,
+    cookies: Arc<CookieStoreMutex>,
+    redirect_count: Arc<AtomicUsize>,
+    proxies: Proxies,
+    accept_invalid_certs: bool,
+    user_agent: String,
+    blocklist: Arc<Blocklist>,
+    doh: bool,
+    resolved: HashMap<String, (Vec<SocketAddr>, Instant)>,
+}
+
+impl ClientOptions {
+    fn build(&self) -> Client {
+        let max_redirects = self.max_redirects;
+        let redirect_count = Arc::clone(&self.redirect_count);
+        let blocklist = Arc::clone(&self.blocklist);
+        let doh = self.doh;
+        let pinned: HashSet<String> = self.resolved.keys().cloned().collect();
+        let policy = Policy::custom(move |attempt| {
+            redirect_count.store(attempt.previous().len(), Ordering::Relaxed);
+            let blocked = attempt
+                .url()
+                .host_str()
+                .is_some_and(|host| blocklist.blocks(host));
+            let unresolved = matches!(attempt.url().host(),
+                Some(url::Host::Domain(host)) if !pinned.contains(host));
+            if blocked || (doh && unresolved) {
+                attempt.stop()
+            } else if attempt.previous().len() > max_redirects {
+                attempt.error("too many redirects")
+            } else {
+                attempt.follow()
+            }
+        });
+
+        let mut builder = Client::builder()
+            .user_agent(&self.user_agent)
+            .cookie_provider(Arc::clone(&self.cookies))
+            .redirect(policy)
+            .gzip(true)
+            .brotli(true)
+            .deflate(true)
+            .timeout(self.timeout)
+            .connect_timeout(Duration::from_secs(CONNECT_TIMEOUT_SECS).min(self.timeout))
+            .tls_info(true)
+            .danger_accept_invalid_certs(self.accept_invalid_certs);
+        for (host, (addresses, _)) in &self.resolved {
+            builder = builder.resolve_to_addrs(host, addresses);
+        }
+
+        match self.proxies.to_reqwest() {
+            Ok(proxies) => {
+                for proxy in proxies {
+                    builder = builder.proxy(proxy);
+                }
+            }
+            Err(e) => eprintln!("Ignoring proxy settings: {}", e),
+        }
+
+        builder.build().unwrap()
+    }
+}
+
+struct LoggedResponse {
+    url: String,
+    status: StatusCode,
+    content_type: Option<String>,
+    bytes: usize,
+    elapsed: Duration,
+    cached: bool,
+}
+
+impl LoggedResponse {
+    fn new(
+        url: &str,
+        status: StatusCode,
+        headers: &HeaderMap,
+        bytes: usize,
+        elapsed: Duration,
+        cached: bool,
+    ) -> Self {
+        LoggedResponse {
+            url: url.to_string(),
+            status,
+            content_type: headers
+                .get(CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from),
+            bytes,
+            elapsed,
+            cached,
+        }
+    }
+}
+
+struct CacheEntry {
+    url: String,
+    status: StatusCode,
+    version: Version,
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+enum HitSource {
+    Tab(usize),
+    Cached(String),
+}
+
+struct SearchHit {
+    source: HitSource,
+    line: usize,
+    text: String,
+}
+
+enum Credential {
+    Basic { user: String, password: String },
+    Bearer(String),
+}
+
+#[derive(Clone)]
+struct PostRequest {
+    url: String,
+    content_type: String,
+    body: String,
+}
+
+#[derive(Default)]
+struct Tab {
     current_url: Option<String>,
-    history: VecDeque<String>,
-    bookmarks: Vec<Bookmark>,
+    title: Option<String>,
+    status: Option<StatusCode>,
+    version: Version,
+    response_headers: HeaderMap,
+    body: Vec<u8>,
+    from_cache: bool,
+    redirects: usize,
+    elapsed: Option<Duration>,
+    peer_certificate: Option<Vec<u8>>,
     page_content: String,
+    raw_html: Option<String>,
+    article_html: Option<String>,
+    reader_mode: bool,
+    render_width: usize,
+    links: Vec<String>,
+    next_page: Option<String>,
+    previous_page: Option<String>,
+    is_feed: bool,
+    is_image: bool,
+    is_binary: bool,
+    is_xml: bool,
+    json_tree: Option<JsonTree>,
+    json_pretty: bool,
+    search_pattern: Option<Pattern>,
+    search_matches: Vec<usize>,
+    search_index: usize,
+    last_post: Option<PostRequest>,
+    posted: bool,
+    last_request: Option<SentRequest>,
+    fragment: Option<String>,
+    view_as: Option<&'static str>,
     scroll_position: usize,
+    navigation: VecDeque<String>,
+    navigation_cursor: usize,
+}
+
+struct Browser {
+    config: Config,
+    client: Client,
+    options: ClientOptions,
+    keymap: Keymap,
+    tabs: Vec<Tab>,
+    active_tab: usize,
+    cache: VecDeque<CacheEntry>,
+    loading: Option<Spinner>,
+    request_started: Instant,
+    last_response: Option<LoggedResponse>,
+    quitting: bool,
+    private: bool,
+    case_sensitive: bool,
+    credentials: HashMap<String, Credential>,
+    public_cookies: Option<Arc<CookieStoreMutex>>,
+    history: VecDeque<HistoryEntry>,
+    visits: HashMap<String, u32>,
+    bookmarks: Vec<Bookmark>,
+    read_later: Vec<QueueEntry>,
+    downloads: Vec<Download>,
+    profile: Option<String>,
+    robots: HashMap<String, robots::Rules>,
+    screen_lines: Vec<(std::ops::Range<usize>, usize)>,
+    clipboard: Option<arboard::Clipboard>,
+    theme: Theme,
+    prefetcher: Prefetcher,
+    log_requests: bool,
+    last_requests: HashMap<String, Instant>,
+    headless: bool,
+    accept: Option<HeaderValue>,
+    show_numbers: bool,
+    closed: VecDeque<SessionTab>,
 }
 
 impl Browser {
     fn new() -> Self {
+        let config = Config::load();
+        let options = ClientOptions {
+            timeout: Self::timeout_from(&config),
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+            cookies: Arc::new(CookieStoreMutex::new(Self::load_cookies(Path::new(
+                COOKIES_FILE,
+            )))),
+            redirect_count: Arc::new(AtomicUsize::new(0)),
+            proxies: Self::proxies_from(&config),
+            accept_invalid_certs: Self::accept_invalid_certs_from(&config),
+            user_agent: config.user_agent.clone(),
+            blocklist: Self::blocklist_from(&config),
+            doh: config.doh_url.is_some(),
+            resolved: HashMap::new(),
+        };
+
         Browser {
-            client: Client::builder()
-                .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
-                .build()
-                .unwrap(),
-            current_url: None,
-            history: VecDeque::with_capacity(MAX_HISTORY),
-            bookmarks: Self::load_bookmarks(),
-            page_content: String::new(),
-            scroll_position: 0,
+            history: Self::load_history(Path::new(HISTORY_FILE), config.max_history),
+            keymap: Keymap::new(&config.keymap, &config.keys),
+            theme: Self::theme_from(&config),
+            prefetcher: Prefetcher::default(),
+            log_requests: false,
+            show_numbers: config.line_numbers,
+            config,
+            client: options.build(),
+            options,
+            tabs: vec![Tab::default()],
+            active_tab: 0,
+            cache: VecDeque::with_capacity(MAX_CACHE_ENTRIES),
+            loading: None,
+            request_started: Instant::now(),
+            last_response: None,
+            quitting: false,
+            private: false,
+            case_sensitive: false,
+            credentials: HashMap::new(),
+            public_cookies: None,
+            visits: Self::load_visits(Path::new(VISITS_FILE)),
+            bookmarks: Self::load_bookmarks(Path::new(BOOKMARKS_FILE)),
+            read_later: Self::load_read_later(Path::new(READ_LATER_FILE)),
+            profile: None,
+            robots: HashMap::new(),
+            screen_lines: Vec::new(),
+            downloads: Vec::new(),
+            clipboard: None,
+            last_requests: HashMap::new(),
+            headless: false,
+            accept: None,
+            closed: VecDeque::with_capacity(MAX_CLOSED_PAGES),
+        }
+    }
+
+    fn tab(&self) -> &Tab {
+        &self.tabs[self.active_tab]
+    }
+
+    fn tab_mut(&mut self) -> &mut Tab {
+        &mut self.tabs[self.active_tab]
+    }
+
+    fn timeout_from(config: &Config) -> Duration {
+        let secs = std::env::var(TIMEOUT_ENV)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(config.timeout_secs);
+        Duration::from_secs(secs)
+    }
+
+    fn blocklist_from(config: &Config) -> Arc<Blocklist> {
+        let blocklist = match &config.blocklist {
+            Some(path) => Blocklist::load(path).unwrap_or_else(|e| {
+                eprintln!("Ignoring blocklist {}: {}", path, e);
+                Blocklist::default()
+            }),
+            None => Blocklist::default(),
+        };
+        Arc::new(blocklist)
+    }
+
+    fn theme_from(config: &Config) -> Theme {
+        Theme::load(&config.theme, &config.colors).unwrap_or_else(|e| {
+            eprintln!("Ignoring theme: {}", e);
+            Theme::load(DEFAULT_THEME, &config.colors).unwrap()
+        })
+    }
+
+    fn proxies_from(config: &Config) -> Proxies {
+        let mut proxies = config.proxy.clone();
+        if let Ok(url) = std::env::var(PROXY_ENV) {
+            proxies.all = Some(url);
+        }
+        proxies
+    }
+
+    fn accept_invalid_certs_from(config: &Config) -> bool {
+        config.accept_invalid_certs || std::env::args().any(|arg| arg == INSECURE_FLAG)
+    }
+
+    fn reload_config(&mut self) {
+        self.config = Config::load();
+        self.keymap = Keymap::new(&self.config.keymap, &self.config.keys);
+        self.theme = Self::theme_from(&self.config);
+        self.options.blocklist = Self::blocklist_from(&self.config);
+        self.options.timeout = Self::timeout_from(&self.config);
+        self.options.proxies = Self::proxies_from(&self.config);
+        self.options.accept_invalid_certs = Self::accept_invalid_certs_from(&self.config);
+        self.options.user_agent = self.config.user_agent.clone();
+        self.options.doh = self.config.doh_url.is_some();
+        self.options.resolved.clear();
+        self.client = self.options.build();
+        self.history.truncate(self.config.max_history);
+    }
+
+    fn set_private(&mut self, private: bool) {
+        if private == self.private {
+            return;
+        }
+        self.private = private;
+        if private {
+            let jar = Arc::new(CookieStoreMutex::new(CookieStore::default()));
+            self.public_cookies = Some(std::mem::replace(&mut self.options.cookies, jar));
+        } else if let Some(cookies) = self.public_cookies.take() {
+            self.options.cookies = cookies;
+        }
+        self.client = self.options.build();
+        self.prefetcher.clear();
+    }
+
+    fn set_proxy(
+        &mut self,
+        scheme: &str,
+        url: Option<&str>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut proxies = self.options.proxies.clone();
+        let url = url.map(String::from);
+        match scheme {
+            "all" => proxies.all = url,
+            "http" => proxies.http = url,
+            "https" => proxies.https = url,
+            _ => return Err(format!("Unknown proxy scheme '{}'", scheme).into()),
+        }
+        proxies.to_reqwest()?;
+        self.options.proxies = proxies;
+        self.client = self.options.build();
+        Ok(())
+    }
+
+    fn show_proxy(&self) {
+        let proxies = &self.options.proxies;
+        if proxies.is_empty() {
+            println!("No proxy configured.");
+            return;
+        }
+        for (scheme, url) in [
+            ("all", &proxies.all),
+            ("http", &proxies.http),
+            ("https", &proxies.https),
+        ] {
+            if let Some(url) = url {
+                println!("{:5} -> {}", scheme, url);
+            }
+        }
+    }
+
+    fn set_timeout(&mut self, secs: u64) {
+        self.options.timeout = Duration::from_secs(secs);
+        self.client = self.options.build();
+    }
+
+    fn set_user_agent(&mut self, user_agent: &str) {
+        let user_agent = UA_PRESETS
+            .iter()
+            .find(|(name, _)| *name == user_agent)
+            .map_or(user_agent, |(_, preset)| *preset);
+        self.options.user_agent = user_agent.to_string();
+        self.client = self.options.build();
+    }
+
+    fn set_max_redirects(&mut self, max: usize) {
+        self.options.max_redirects = max;
+        self.client = self.options.build();
+    }
+
+    fn state_file(&self, name: &str) -> PathBuf {
+        match &self.profile {
+            Some(profile) => Path::new(PROFILES_DIR).join(profile).join(name),
+            None => PathBuf::from(name),
+        }
+    }
+
+    fn create_state_file(&self, name: &str) -> io::Result<File> {
+        self.open_state_file(
+            name,
+            OpenOptions::new().write(true).create(true).truncate(true),
+        )
+    }
+
+    fn open_state_file(&self, name: &str, options: &OpenOptions) -> io::Result<File> {
+        let path = self.state_file(name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        options.open(path)
+    }
+
+    fn switch_profile(&mut self, profile: Option<&str>) -> io::Result<()> {
+        if let Some(name) = profile {
+            let valid = name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+            if name.is_empty() || !valid {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "profile names may only contain letters, digits, '-' and '_'",
+                ));
+            }
+        }
+        self.save_cookies()?;
+        if self.tabs.iter().any(|tab| tab.current_url.is_some()) {
+            self.save_session()?;
+        }
+        self.profile = profile.map(String::from);
+        self.tabs = vec![Tab::default()];
+        self.active_tab = 0;
+        self.closed.clear();
+        self.credentials.clear();
+        self.robots.clear();
+        self.clear_cache();
+
+        let cookies = Self::load_cookies(&self.state_file(COOKIES_FILE));
+        let cookies = Arc::new(CookieStoreMutex::new(cookies));
+        if self.private {
+            self.public_cookies = Some(cookies);
+        } else {
+            self.options.cookies = cookies;
+            self.client = self.options.build();
+            self.prefetcher.clear();
+        }
+        self.bookmarks = Self::load_bookmarks(&self.state_file(BOOKMARKS_FILE));
+        self.read_later = Self::load_read_later(&self.state_file(READ_LATER_FILE));
+        self.history = Self::load_history(&self.state_file(HISTORY_FILE), self.config.max_history);
+        self.visits = Self::load_visits(&self.state_file(VISITS_FILE));
+        Ok(())
+    }
+
+    fn load_cookies(path: &Path) -> CookieStore {
+        if let Ok(file) = File::open(path) {
+            CookieStore::load_json_all(BufReader::new(file)).unwrap_or_default()
+        } else {
+            CookieStore::default()
         }
     }
 
-    fn load_bookmarks() -> Vec<Bookmark> {
-        if let Ok(file) = File::open(BOOKMARKS_FILE) {
+    fn save_cookies(&self) -> io::Result<()> {
+        let mut file = self.create_state_file(COOKIES_FILE)?;
+        let cookies = self
+            .public_cookies
+            .as_ref()
+            .unwrap_or(&self.options.cookies);
+        let store = cookies
+            .lock()
+            .map_err(|_| io::Error::other("cookie store lock poisoned"))?;
+        store
+            .save_incl_expired_and_nonpersistent_json(&mut file)
+            .map_err(io::Error::other)?;
+        Ok(())
+    }
+
+    fn load_bookmarks(path: &Path) -> Vec<Bookmark> {
+        if let Ok(file) = File::open(path) {
             serde_json::from_reader(file).unwrap_or_default()
         } else {
             Vec::new()
@@ -53,247 +679,4075 @@ impl Browser {
     }
 
     fn save_bookmarks(&self) -> io::Result<()> {
-        let file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(BOOKMARKS_FILE)?;
+        let file = self.create_state_file(BOOKMARKS_FILE)?;
         serde_json::to_writer_pretty(file, &self.bookmarks)?;
         Ok(())
     }
 
-    fn add_to_history(&mut self, url: String) {
-        if let Some(pos) = self.history.iter().position(|x| x == &url) {
-            self.history.remove(pos);
+    fn load_read_later(path: &Path) -> Vec<QueueEntry> {
+        if let Ok(file) = File::open(path) {
+            serde_json::from_reader(file).unwrap_or_default()
+        } else {
+            Vec::new()
         }
+    }
 
-        if self.history.len() >= MAX_HISTORY {
-            self.history.pop_back();
-        }
-        self.history.push_front(url);
+    fn save_read_later(&self) -> io::Result<()> {
+        let file = self.create_state_file(READ_LATER_FILE)?;
+        serde_json::to_writer_pretty(file, &self.read_later)?;
+        Ok(())
     }
 
-    fn navigate(&mut self, url: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let url = if !url.starts_with("http") {
-            format!("https://{}", url)
+    fn load_history(path: &Path, max_history: usize) -> VecDeque<HistoryEntry> {
+        let mut history: VecDeque<HistoryEntry> = if let Ok(file) = File::open(path) {
+            serde_json::from_reader(file).unwrap_or_default()
         } else {
-            url.to_string()
+            VecDeque::new()
         };
+        history.truncate(max_history);
+        history
+    }
 
-        let response = self.client.get(&url).send()?;
-        let url_clone = url.clone();
-        self.handle_response(response, &url_clone)?;
-        self.add_to_history(url.clone());
-        self.current_url = Some(url);
-        self.scroll_position = 0;
+    fn save_history(&self) -> io::Result<()> {
+        let file = self.create_state_file(HISTORY_FILE)?;
+        serde_json::to_writer_pretty(file, &self.history)?;
         Ok(())
     }
 
-    fn handle_response(
-        &mut self,
-        response: Response,
-        _url: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let content_type = response
-            .headers()
-            .get("content-type")
-            .and_then(|v| v.to_str().ok())
-            .unwrap_or("");
-
-        if content_type.contains("text/html") {
-            let text = response.text()?;
-            self.page_content = self.render_html(&text);
-        } else if content_type.contains("application/json") {
-            let json: serde_json::Value = response.json()?;
-            self.page_content = serde_json::to_string_pretty(&json)?;
+    fn load_visits(path: &Path) -> HashMap<String, u32> {
+        if let Ok(file) = File::open(path) {
+            serde_json::from_reader(file).unwrap_or_default()
         } else {
-            self.page_content =
-                format!("Content-Type '{}' not supported for display", content_type);
+            HashMap::new()
         }
+    }
 
-        self.display_page()?;
+    fn save_visits(&self) -> io::Result<()> {
+        let file = self.create_state_file(VISITS_FILE)?;
+        serde_json::to_writer_pretty(file, &self.visits)?;
         Ok(())
     }
 
-    fn render_html(&self, html: &str) -> String {
-        html2text::from_read(html.as_bytes(), 100)
+    fn load_session(&self) -> Session {
+        if let Ok(file) = File::open(self.state_file(SESSION_FILE)) {
+            serde_json::from_reader(file).unwrap_or_default()
+        } else {
+            Session::default()
+        }
     }
 
-    fn display_page(&self) -> io::Result<()> {
-        execute!(io::stdout(), Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+    fn save_session(&self) -> io::Result<()> {
+        if self.private {
+            return Ok(());
+        }
+        let mut session = Session::default();
+        for (i, tab) in self.tabs.iter().enumerate() {
+            if let Some(url) = &tab.current_url {
+                if i == self.active_tab {
+                    session.active_tab = session.tabs.len();
+                }
+                session.tabs.push(SessionTab {
+                    url: url.clone(),
+                    scroll_position: tab.scroll_position,
+                });
+            }
+        }
+        let file = self.create_state_file(SESSION_FILE)?;
+        serde_json::to_writer_pretty(file, &session)?;
+        Ok(())
+    }
 
-        execute!(
-            io::stdout(),
-            SetBackgroundColor(Color::Blue),
-            SetForegroundColor(Color::White)
-        )?;
+    fn restore_session(&mut self) -> io::Result<bool> {
+        let session = self.load_session();
+        if session.tabs.is_empty() {
+            println!("No saved session.");
+            return Ok(false);
+        }
 
-        let terminal_width = crossterm::terminal::size()?.0 as usize;
-        let header = format!(" Rust Web Browser ");
-        let padding = " ".repeat(terminal_width - header.len());
-        println!("{}{}", header, padding);
+        self.tabs = Vec::new();
+        for saved in &session.tabs {
+            self.tabs.push(Tab::default());
+            self.active_tab = self.tabs.len() - 1;
+            match self.load(&saved.url) {
+                Ok(url) => self.tab_mut().navigation.push_front(url),
+                Err(e) => {
+                    self.report_error(&format!("Could not restore {}: {}", saved.url, e))?;
+                    self.tab_mut().current_url = Some(saved.url.clone());
+                }
+            }
+            self.tab_mut().scroll_position = saved.scroll_position;
+        }
+        self.active_tab = session.active_tab.min(self.tabs.len() - 1);
+        self.display_page()?;
+        Ok(true)
+    }
 
-        execute!(
-            io::stdout(),
-            ResetColor,
-            SetBackgroundColor(Color::Black),
-            SetForegroundColor(Color::Green)
-        )?;
+    fn omnibox(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if !io::stdin().is_terminal() {
+            println!("The address bar needs an interactive terminal. Use g URL instead.");
+            return Ok(());
+        }
+        let mut suggestions: Vec<omnibox::Suggestion> = self
+            .bookmarks
+            .iter()
+            .map(|bookmark| omnibox::Suggestion {
+                title: bookmark.title.clone(),
+                url: bookmark.url.clone(),
+                bookmarked: true,
+            })
+            .collect();
+        let mut history: Vec<&HistoryEntry> = self.history.iter().collect();
+        history.sort_by_key(|entry| std::cmp::Reverse(self.visits.get(&entry.url).copied()));
+        for entry in history {
+            if !suggestions
+                .iter()
+                .any(|suggestion| suggestion.url == entry.url)
+            {
+                let visited: DateTime<Local> = entry.visited.into();
+                suggestions.push(omnibox::Suggestion {
+                    title: visited.format("%Y-%m-%d %H:%M").to_string(),
+                    url: entry.url.clone(),
+                    bookmarked: false,
+                });
+            }
+        }
 
-        let url = self.current_url.as_deref().unwrap_or("No URL");
-        println!("└─ URL: {}\n", url);
+        match omnibox::run(&suggestions)? {
+            Some(url) => self.navigate(&url),
+            None => Ok(self.display_page()?),
+        }
+    }
 
-        execute!(io::stdout(), ResetColor)?;
+    fn most_visited(&self) -> Vec<(&str, u32)> {
+        let mut ranked: Vec<(&str, u32)> = self
+            .visits
+            .iter()
+            .map(|(url, count)| (url.as_str(), *count))
+            .collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        ranked.truncate(TOP_VISITED);
+        ranked
+    }
+
+    fn add_to_history(&mut self, url: String) -> io::Result<()> {
+        if self.private {
+            return Ok(());
+        }
+        *self.visits.entry(url.clone()).or_insert(0) += 1;
+        self.save_visits()?;
+        if let Some(pos) = self.history.iter().position(|x| x.url == url) {
+            self.history.remove(pos);
+        }
+
+        self.history
+            .truncate(self.config.max_history.saturating_sub(1));
+        self.history.push_front(HistoryEntry {
+            url,
+            visited: Utc::now(),
+        });
+        self.save_history()
+    }
 
-        let lines: Vec<&str> = self.page_content.lines().collect();
-        let terminal_height = crossterm::terminal::size()?.1 as usize - 7;
+    fn expand_bang(&self, input: &str) -> Result<String, String> {
+        let (bang, query) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+        let template = self
+            .config
+            .bangs
+            .get(bang)
+            .map(String::as_str)
+            .or_else(|| {
+                DEFAULT_BANGS
+                    .iter()
+                    .find(|(name, _)| *name == bang)
+                    .map(|(_, template)| *template)
+            })
+            .ok_or_else(|| format!("Unknown search shortcut !{}", bang))?;
+        let query: String = url::form_urlencoded::byte_serialize(query.trim().as_bytes()).collect();
+        Ok(template.replace("{query}", &query))
+    }
 
-        let max_scroll = if lines.len() > terminal_height {
-            lines.len() - terminal_height
+    fn normalize_url(url: &str) -> String {
+        if url.starts_with("http://") || url.starts_with("https://") || url.starts_with("file://") {
+            url.to_string()
+        } else if let Some(url) = fix_scheme(url) {
+            url
+        } else if let Some(url) = Self::local_file_url(url) {
+            url
         } else {
-            0
+            format!("https://{}", url)
+        }
+    }
+
+    fn local_file_url(path: &str) -> Option<String> {
+        if !path.starts_with(['/', '.']) {
+            return None;
+        }
+        let path = std::fs::canonicalize(path).ok()?;
+        Url::from_file_path(path).ok().map(String::from)
+    }
+
+    fn content_type_for(path: &Path) -> &'static str {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        match extension.as_str() {
+            "html" | "htm" | "xhtml" => "text/html",
+            "md" | "markdown" => "text/markdown",
+            "json" => "application/json",
+            "xml" => "application/xml",
+            "rss" => "application/rss+xml",
+            "atom" => "application/atom+xml",
+            "png" => "image/png",
+            "jpg" | "jpeg" => "image/jpeg",
+            "gif" => "image/gif",
+            "webp" => "image/webp",
+            _ => "text/plain",
+        }
+    }
+
+    fn navigate(&mut self, url: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let scheme_implied = !url.starts_with('!') && !url.contains("://");
+        let url = match url.strip_prefix('!') {
+            Some(bang) => self.expand_bang(bang)?,
+            None => Self::normalize_url(url),
         };
 
-        let effective_scroll = std::cmp::min(self.scroll_position, max_scroll);
-        let visible_lines = &lines[effective_scroll..];
+        if !self.blocklist_allow(&url)? {
+            return Err(format!("{} is on the blocklist", url).into());
+        }
+        if self.config.respect_robots && !self.robots_allow(&url)? {
+            return Err(format!("{} is disallowed by robots.txt", url).into());
+        }
 
-        for (i, line) in visible_lines.iter().enumerate().take(terminal_height) {
-            if line.trim().starts_with('#') {
-                execute!(io::stdout(), SetForegroundColor(Color::Cyan))?;
-                println!("{:4} │ {}", i + effective_scroll + 1, line);
-                execute!(io::stdout(), ResetColor)?;
-            } else if line.contains("http") || line.contains("www.") {
-                execute!(io::stdout(), SetForegroundColor(Color::Blue))?;
-                println!("{:4} │ {}", i + effective_scroll + 1, line);
-                execute!(io::stdout(), ResetColor)?;
-            } else {
-                execute!(io::stdout(), SetForegroundColor(Color::White))?;
-                println!("{:4} │ {}", i + effective_scroll + 1, line);
+        let leaving = self.leaving_page();
+        let mut insecure = false;
+        let url = match self.load(&url) {
+            Ok(url) => url,
+            Err(e) => {
+                let connect_failed = e
+                    .downcast_ref::<reqwest::Error>()
+                    .is_some_and(|e| e.is_connect());
+                let fallback = self
+                    .http_fallback(&url)
+                    .filter(|_| connect_failed && scheme_implied);
+                match fallback {
+                    Some(http) => {
+                        insecure = true;
+                        self.load(&http)?
+                    }
+                    None => match self.suggest_url(&url).filter(|_| connect_failed) {
+                        Some(suggestion) if Self::confirm_suggestion(&url, &suggestion)? => {
+                            self.load(&suggestion)?
+                        }
+                        _ => return Err(e),
+                    },
+                }
+            }
+        };
+        let tab = self.tab_mut();
+        tab.navigation.drain(..tab.navigation_cursor);
+        tab.navigation_cursor = 0;
+        if tab.navigation.front() != Some(&url) {
+            tab.navigation.push_front(url.clone());
+            tab.navigation.truncate(MAX_NAVIGATION);
+        }
+        if leaving.as_ref().is_some_and(|page| page.url != url) {
+            self.remember_closed(leaving);
+        }
+        self.add_to_history(url.clone())?;
+        if insecure {
+            self.warn_insecure(&url)?;
+        }
+        Ok(())
+    }
+
+    fn throttle(&mut self, url: &str) {
+        let delay = Duration::from_millis(self.config.host_delay_ms);
+        let host = match Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(String::from))
+        {
+            Some(host) if !delay.is_zero() => host,
+            _ => return,
+        };
+        if let Some(last) = self.last_requests.get(&host) {
+            let wait = delay.saturating_sub(last.elapsed());
+            if !wait.is_zero() {
+                std::thread::sleep(wait);
             }
         }
+        self.last_requests.insert(host, Instant::now());
+    }
 
-        execute!(
-            io::stdout(),
-            cursor::MoveTo(0, (terminal_height + 5) as u16),
-            SetBackgroundColor(Color::DarkGrey),
-            SetForegroundColor(Color::White)
-        )?;
+    fn lookup(&self, host: &str) -> Result<Vec<IpAddr>, Box<dyn std::error::Error>> {
+        match &self.config.doh_url {
+            Some(endpoint) => Ok(self.doh_lookup(endpoint, host)?.0),
+            None => Ok((host, 0)
+                .to_socket_addrs()?
+                .map(|address| address.ip())
+                .collect()),
+        }
+    }
 
-        let status = format!(
-            " Lines: {} | Position: {} ",
-            lines.len(),
-            effective_scroll + 1
-        );
-        let status_padding = " ".repeat(terminal_width - status.len());
-        println!("{}{}", status, status_padding);
+    fn doh_lookup(
+        &self,
+        endpoint: &str,
+        host: &str,
+    ) -> Result<(Vec<IpAddr>, Duration), Box<dyn std::error::Error>> {
+        let client = Client::builder().timeout(self.options.timeout).build()?;
+        doh::lookup(&client, endpoint, host)
+    }
 
-        execute!(
-            io::stdout(),
-            ResetColor,
-            SetForegroundColor(Color::DarkGrey)
-        )?;
-        println!("\n[Press 'h' for help] [w/s to scroll] [q to quit]");
+    fn resolve_via_doh(&mut self, url: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let endpoint = match &self.config.doh_url {
+            Some(endpoint) => endpoint.clone(),
+            None => return Ok(()),
+        };
+        let host = match Url::parse(url) {
+            Ok(url) => match url.host() {
+                Some(url::Host::Domain(host)) => host.to_string(),
+                _ => return Ok(()),
+            },
+            Err(_) => return Ok(()),
+        };
+        let now = Instant::now();
+        let fresh = self
+            .options
+            .resolved
+            .get(&host)
+            .is_some_and(|(_, expires)| *expires > now);
+        if fresh {
+            return Ok(());
+        }
 
-        io::stdout().flush()?;
+        let (addresses, ttl) = self.doh_lookup(&endpoint, &host)?;
+        let addresses = addresses
+            .into_iter()
+            .map(|ip| SocketAddr::new(ip, 0))
+            .collect();
+        self.options
+            .resolved
+            .retain(|_, (_, expires)| *expires > now);
+        self.options.resolved.insert(host, (addresses, now + ttl));
+        self.client = self.options.build();
         Ok(())
     }
 
-    fn add_bookmark(&mut self, title: &str) -> io::Result<()> {
-        if let Some(url) = &self.current_url {
-            self.bookmarks.push(Bookmark {
-                title: title.to_string(),
-                url: url.clone(),
-            });
-            self.save_bookmarks()?;
+    fn http_fallback(&self, url: &str) -> Option<String> {
+        if self.config.https_only {
+            return None;
         }
-        Ok(())
+        let mut parsed = Url::parse(url).ok()?;
+        let host = parsed.host_str()?.to_string();
+        if parsed.scheme() != "https" || self.lookup(&host).map_or(true, |found| found.is_empty()) {
+            return None;
+        }
+        parsed.set_scheme("http").ok()?;
+        Some(parsed.to_string())
+    }
+
+    fn warn_insecure(&mut self, url: &str) -> io::Result<()> {
+        let host = Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(String::from));
+        let message = format!(
+            "{} could not be reached over HTTPS, so it was loaded over plain HTTP. \
+             The connection is not secure.",
+            host.as_deref().unwrap_or(url)
+        );
+        toast::show(&message, Color::DarkYellow)?;
+        if io::stdin().is_terminal() {
+            self.display_page()?;
+        }
+        Ok(())
+    }
+
+    fn suggest_url(&self, url: &str) -> Option<String> {
+        let mut parsed = Url::parse(url).ok()?;
+        let host = match parsed.host() {
+            Some(url::Host::Domain(host)) => host.to_string(),
+            _ => return None,
+        };
+        if self.lookup(&host).is_ok() {
+            return None;
+        }
+        let candidates = if !host.contains('.') {
+            vec![format!("{}.com", host), format!("www.{}.com", host)]
+        } else if !host.starts_with("www.") {
+            vec![format!("www.{}", host)]
+        } else {
+            Vec::new()
+        };
+        let host = candidates
+            .into_iter()
+            .find(|candidate| self.lookup(candidate).is_ok_and(|found| !found.is_empty()))?;
+        parsed.set_host(Some(&host)).ok()?;
+        Some(parsed.to_string())
+    }
+
+    fn confirm_suggestion(url: &str, suggestion: &str) -> io::Result<bool> {
+        print!(
+            "Could not resolve {}. Try {} instead? [y/N] ",
+            url, suggestion
+        );
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        Ok(answer.trim().eq_ignore_ascii_case("y"))
+    }
+
+    fn robots_allow(&mut self, url: &str) -> io::Result<bool> {
+        let url = match Url::parse(url) {
+            Ok(url) if url.scheme().starts_with("http") => url,
+            _ => return Ok(true),
+        };
+        let origin = url.origin().ascii_serialization();
+        if !self.robots.contains_key(&origin) {
+            if self.resolve_via_doh(url.as_str()).is_err() {
+                return Ok(true);
+            }
+            let limit = self.config.max_body_bytes;
+            let body = self
+                .client
+                .get(format!("{}/robots.txt", origin))
+                .send()
+                .ok()
+                .filter(|response| response.status().is_success())
+                .and_then(|response| read_limited(response, limit).ok())
+                .unwrap_or_default();
+            self.robots.insert(
+                origin.clone(),
+                robots::Rules::parse(&String::from_utf8_lossy(&body)),
+            );
+        }
+        if self.robots[&origin].is_allowed(url.path()) {
+            return Ok(true);
+        }
+
+        print!(
+            "robots.txt for {} disallows {}. Proceed anyway? [y/N] ",
+            origin,
+            url.path()
+        );
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        Ok(answer.trim().eq_ignore_ascii_case("y"))
+    }
+
+    fn blocklist_allow(&mut self, url: &str) -> io::Result<bool> {
+        let host = match Self::host_of(url) {
+            Some(host) if self.options.blocklist.blocks(&host) => host,
+            _ => return Ok(true),
+        };
+
+        print!(
+            "{} is on the tracker blocklist. Load it anyway? [y/N] ",
+            host
+        );
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if !answer.trim().eq_ignore_ascii_case("y") {
+            return Ok(false);
+        }
+        Arc::make_mut(&mut self.options.blocklist).allow(&host);
+        self.client = self.options.build();
+        Ok(true)
+    }
+
+    fn check_blocklist(&self, url: &str) -> Result<(), String> {
+        match Self::host_of(url) {
+            Some(host) if self.options.blocklist.blocks(&host) => {
+                Err(format!("{} is on the blocklist", host))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn show_blocklist(&self) {
+        let blocklist = &self.options.blocklist;
+        match &self.config.blocklist {
+            Some(path) if !blocklist.is_empty() => {
+                println!("Blocking {} hosts from {}", blocklist.len(), path)
+            }
+            Some(path) => println!("No hosts loaded from {}", path),
+            None => println!("No blocklist configured. Set blocklist in {}.", CONFIG_FILE),
+        }
+    }
+
+    fn new_tab(&mut self, url: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+        self.tabs.push(Tab::default());
+        self.active_tab = self.tabs.len() - 1;
+        match url
+            .map(String::from)
+            .or_else(|| self.config.home_url.clone())
+        {
+            Some(url) => self.navigate(&url),
+            None => Ok(self.display_page()?),
+        }
+    }
+
+    fn switch_tab(&mut self, offset: isize) -> io::Result<()> {
+        if self.tabs.len() == 1 {
+            println!("Only one tab is open.");
+            return Ok(());
+        }
+        let count = self.tabs.len() as isize;
+        self.active_tab = (self.active_tab as isize + offset).rem_euclid(count) as usize;
+        self.display_page()
+    }
+
+    fn close_tab(&mut self) -> io::Result<()> {
+        if self.tabs.len() == 1 {
+            println!("Cannot close the last tab.");
+            return Ok(());
+        }
+        let closing = self.leaving_page();
+        self.remember_closed(closing);
+        self.tabs.remove(self.active_tab);
+        self.active_tab = self.active_tab.min(self.tabs.len() - 1);
+        self.display_page()
+    }
+
+    fn leaving_page(&self) -> Option<SessionTab> {
+        if self.private {
+            return None;
+        }
+        self.tab().current_url.clone().map(|url| SessionTab {
+            url,
+            scroll_position: self.tab().scroll_position,
+        })
+    }
+
+    fn remember_closed(&mut self, page: Option<SessionTab>) {
+        if let Some(page) = page {
+            self.closed.retain(|closed| closed.url != page.url);
+            self.closed.push_front(page);
+            self.closed.truncate(MAX_CLOSED_PAGES);
+        }
+    }
+
+    fn reopen(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let page = match self.closed.pop_front() {
+            Some(page) => page,
+            None => {
+                println!("No recently closed pages.");
+                return Ok(());
+            }
+        };
+        self.tabs.push(Tab::default());
+        self.active_tab = self.tabs.len() - 1;
+        match self.load(&page.url) {
+            Ok(url) => self.tab_mut().navigation.push_front(url),
+            Err(e) => {
+                self.tab_mut().current_url = Some(page.url);
+                return Err(e);
+            }
+        }
+        self.tab_mut().scroll_position = page.scroll_position;
+        self.display_page()?;
+        Ok(())
+    }
+
+    fn tab_strip(&self) -> String {
+        self.tabs
+            .iter()
+            .enumerate()
+            .map(|(i, tab)| {
+                let label = match &tab.title {
+                    Some(title) => title.chars().take(TAB_LABEL_WIDTH).collect(),
+                    None => tab
+                        .current_url
+                        .as_deref()
+                        .and_then(|url| Url::parse(url).ok())
+                        .and_then(|url| url.host_str().map(String::from))
+                        .unwrap_or_else(|| "new tab".to_string()),
+                };
+                if i == self.active_tab {
+                    format!("[{}:{}]", i + 1, label)
+                } else {
+                    format!(" {}:{} ", i + 1, label)
+                }
+            })
+            .collect()
+    }
+
+    fn load(&mut self, url: &str) -> Result<String, Box<dyn std::error::Error>> {
+        self.fetch(url, false)
+    }
+
+    fn fetch(&mut self, url: &str, hard: bool) -> Result<String, Box<dyn std::error::Error>> {
+        if url.starts_with("file://") {
+            return self.load_file(url);
+        }
+        self.last_response = None;
+        let result = self.fetch_remote(url, hard);
+        self.log_request("GET", url, result.as_ref().err().map(|e| e.to_string()));
+        result
+    }
+
+    fn fetch_remote(
+        &mut self,
+        url: &str,
+        hard: bool,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        if !hard {
+            if let Some(page) = self.prefetcher.take(url) {
+                return self.show_prefetched(url, page);
+            }
+        }
+        self.throttle(url);
+        self.resolve_via_doh(url)?;
+
+        let mut response = self.send(self.get_request(url, hard), url, self.config.retries)?;
+        if response.status() == StatusCode::UNAUTHORIZED && self.prompt_credentials(url)? {
+            response = self.send(self.get_request(url, hard), url, self.config.retries)?;
+        }
+
+        let final_url = response.url().to_string();
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some(entry) = self.cache_take(&final_url) {
+                self.begin_fetched_page(&final_url, url);
+                let elapsed = self.request_started.elapsed();
+                self.last_response = Some(LoggedResponse::new(
+                    &final_url,
+                    entry.status,
+                    &entry.headers,
+                    entry.body.len(),
+                    elapsed,
+                    true,
+                ));
+                let tab = self.tab_mut();
+                tab.status = Some(entry.status);
+                tab.version = entry.version;
+                tab.response_headers = entry.headers.clone();
+                tab.body = entry.body.clone();
+                tab.from_cache = true;
+                tab.elapsed = Some(elapsed);
+                self.cache.push_front(entry);
+                self.render_body()?;
+                return Ok(final_url);
+            }
+        }
+
+        self.handle_response(response, url)?;
+        if !self.private && self.tab().status.is_some_and(|status| status.is_success()) {
+            self.cache_insert(&final_url);
+        }
+        Ok(final_url)
+    }
+
+    fn show_prefetched(
+        &mut self,
+        url: &str,
+        page: prefetch::Page,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        self.begin_fetched_page(&page.url, url);
+        self.last_response = Some(LoggedResponse::new(
+            &page.url,
+            page.status,
+            &page.headers,
+            page.body.len(),
+            page.elapsed,
+            true,
+        ));
+        let tab = self.tab_mut();
+        tab.status = Some(page.status);
+        tab.version = page.version;
+        tab.response_headers = page.headers;
+        tab.body = page.body;
+        tab.redirects = 0;
+        tab.peer_certificate = None;
+        tab.elapsed = Some(page.elapsed);
+        tab.from_cache = true;
+        self.render_body()?;
+        if !self.private {
+            self.cache_insert(&page.url);
+        }
+        Ok(page.url)
+    }
+
+    fn load_file(&mut self, url: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let path = Url::parse(url)?
+            .to_file_path()
+            .map_err(|_| format!("Not a local file: {}", url))?;
+        self.request_started = Instant::now();
+        let body = std::fs::read(&path)?;
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static(Self::content_type_for(&path)),
+        );
+
+        self.begin_page(url);
+        let elapsed = self.request_started.elapsed();
+        let tab = self.tab_mut();
+        tab.status = None;
+        tab.version = Version::default();
+        tab.response_headers = headers;
+        tab.body = body;
+        tab.redirects = 0;
+        tab.peer_certificate = None;
+        tab.elapsed = Some(elapsed);
+        self.render_body()?;
+        Ok(url.to_string())
+    }
+
+    fn get_request(&self, url: &str, hard: bool) -> RequestBuilder {
+        let mut request = self.negotiate(self.authorize(self.client.get(url), url));
+        if hard {
+            return request.header(CACHE_CONTROL, "no-cache");
+        }
+        if let Some(entry) = self.cache.iter().find(|entry| entry.url == url) {
+            if let Some(etag) = entry.headers.get(ETAG) {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = entry.headers.get(LAST_MODIFIED) {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        request
+    }
+
+    fn curl_command(&self) -> Option<String> {
+        let request = self.tab().last_request.as_ref()?;
+        let mut options = vec![
+            "-L".to_string(),
+            "--compressed".to_string(),
+            format!("--max-redirs {}", self.options.max_redirects),
+            format!("-A {}", curl::quote(&self.options.user_agent)),
+        ];
+        if self.options.accept_invalid_certs {
+            options.push("-k".to_string());
+        }
+        let proxies = &self.options.proxies;
+        let proxy = if request.url.starts_with("https:") {
+            proxies.https.as_ref()
+        } else {
+            proxies.http.as_ref()
+        };
+        if let Some(proxy) = proxy.or(proxies.all.as_ref()) {
+            options.push(format!("-x {}", curl::quote(proxy)));
+        }
+        Some(request.to_command(&options))
+    }
+
+    fn host_of(url: &str) -> Option<String> {
+        Url::parse(url).ok()?.host_str().map(String::from)
+    }
+
+    fn authorize(&self, request: RequestBuilder, url: &str) -> RequestBuilder {
+        let credential = Self::host_of(url).and_then(|host| self.credentials.get(&host));
+        match credential {
+            Some(Credential::Basic { user, password }) => request.basic_auth(user, Some(password)),
+            Some(Credential::Bearer(token)) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    fn negotiate(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.accept {
+            Some(accept) => request.header(ACCEPT, accept.clone()),
+            None => request,
+        }
+    }
+
+    fn set_credential(&mut self, credential: Option<Credential>) -> Result<String, String> {
+        let host = self
+            .tab()
+            .current_url
+            .as_deref()
+            .and_then(Self::host_of)
+            .ok_or("Open a page on the host first")?;
+        match credential {
+            Some(credential) => self.credentials.insert(host.clone(), credential),
+            None => self.credentials.remove(&host),
+        };
+        Ok(host)
+    }
+
+    fn prompt_credentials(&mut self, url: &str) -> io::Result<bool> {
+        self.loading = None;
+        let host = match Self::host_of(url) {
+            Some(host) => host,
+            None => return Ok(false),
+        };
+
+        println!("{} requires authentication.", host);
+        match Self::read_basic_auth("")? {
+            Some((user, password)) => {
+                self.credentials
+                    .insert(host, Credential::Basic { user, password });
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn read_basic_auth(user: &str) -> io::Result<Option<(String, String)>> {
+        let user = if user.is_empty() {
+            print!("Username (empty to cancel): ");
+            io::stdout().flush()?;
+            let mut user = String::new();
+            io::stdin().read_line(&mut user)?;
+            user.trim().to_string()
+        } else {
+            user.to_string()
+        };
+        if user.is_empty() {
+            return Ok(None);
+        }
+        let password = prompt::read_secret("Password: ")?;
+        Ok(Some((user, password)))
+    }
+
+    fn start_spinner(&mut self, url: &str) {
+        if !self.headless {
+            self.loading = Some(Spinner::start(&format!("Loading {}", url)));
+        }
+    }
+
+    fn send_interruptible(
+        &self,
+        request: RequestBuilder,
+    ) -> io::Result<Option<reqwest::Result<Response>>> {
+        self.interruptible(move || request.send())
+    }
+
+    fn interruptible<T: Send + 'static>(
+        &self,
+        work: impl FnOnce() -> T + Send + 'static,
+    ) -> io::Result<Option<T>> {
+        if self.headless || !io::stdin().is_terminal() {
+            return Ok(Some(work()));
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(work());
+        });
+
+        enable_raw_mode()?;
+        let result = loop {
+            match receiver.recv_timeout(CANCEL_POLL_INTERVAL) {
+                Ok(result) => break Ok(Some(result)),
+                Err(RecvTimeoutError::Disconnected) => {
+                    break Err(io::Error::other("request thread stopped"))
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+            }
+            match poll(Duration::ZERO) {
+                Ok(true) => match read() {
+                    Ok(Event::Key(key)) if is_cancel_key(key) => break Ok(None),
+                    Ok(_) => {}
+                    Err(e) => break Err(e),
+                },
+                Ok(false) => {}
+                Err(e) => break Err(e),
+            }
+        };
+        disable_raw_mode()?;
+        result
+    }
+
+    fn send(
+        &mut self,
+        request: RequestBuilder,
+        url: &str,
+        retries: u32,
+    ) -> Result<Response, Box<dyn std::error::Error>> {
+        self.check_blocklist(url)?;
+        let sent = request.try_clone().and_then(|request| request.build().ok());
+        if let Some(sent) = &sent {
+            self.tab_mut().last_request = Some(SentRequest::record(sent));
+        }
+        let response = self.send_with_retries(request, url, retries)?;
+        let result = self.follow_stopped_redirects(response, sent.as_ref(), url, retries);
+        if result.is_err() {
+            self.loading = None;
+        }
+        result
+    }
+
+    fn follow_stopped_redirects(
+        &mut self,
+        mut response: Response,
+        sent: Option<&reqwest::blocking::Request>,
+        url: &str,
+        retries: u32,
+    ) -> Result<Response, Box<dyn std::error::Error>> {
+        let mut redirects = self.tab().redirects;
+        while let Some(location) = self.stopped_redirect(&response) {
+            redirects += 1;
+            if redirects > self.options.max_redirects {
+                return Err(format!(
+                    "Request to {} exceeded the redirect limit of {}",
+                    url, self.options.max_redirects
+                )
+                .into());
+            }
+            self.check_blocklist(location.as_str())?;
+            self.resolve_via_doh(location.as_str())?;
+            let request = self.redirected_request(response.status(), sent, &location);
+            response = self.send_with_retries(request, location.as_str(), retries)?;
+            redirects += self.tab().redirects;
+        }
+        self.tab_mut().redirects = redirects;
+        Ok(response)
+    }
+
+    fn stopped_redirect(&self, response: &Response) -> Option<Url> {
+        let redirect = matches!(
+            response.status(),
+            StatusCode::MOVED_PERMANENTLY
+                | StatusCode::FOUND
+                | StatusCode::SEE_OTHER
+                | StatusCode::TEMPORARY_REDIRECT
+                | StatusCode::PERMANENT_REDIRECT
+        );
+        if !redirect {
+            return None;
+        }
+        let location = response.headers().get(LOCATION)?.to_str().ok()?;
+        response.url().join(location).ok()
+    }
+
+    fn redirected_request(
+        &self,
+        status: StatusCode,
+        sent: Option<&reqwest::blocking::Request>,
+        location: &Url,
+    ) -> RequestBuilder {
+        let url = location.as_str();
+        let keeps_method = matches!(
+            status,
+            StatusCode::TEMPORARY_REDIRECT | StatusCode::PERMANENT_REDIRECT
+        );
+        let original = match sent.filter(|_| keeps_method) {
+            Some(original) => original,
+            None => return self.negotiate(self.authorize(self.client.get(url), url)),
+        };
+        let mut request = self.client.request(original.method().clone(), url);
+        for (name, value) in original.headers() {
+            if name != AUTHORIZATION {
+                request = request.header(name, value);
+            }
+        }
+        if let Some(body) = original.body().and_then(|body| body.as_bytes()) {
+            request = request.body(body.to_vec());
+        }
+        self.authorize(request, url)
+    }
+
+    fn send_with_retries(
+        &mut self,
+        mut request: RequestBuilder,
+        url: &str,
+        retries: u32,
+    ) -> Result<Response, Box<dyn std::error::Error>> {
+        self.options.redirect_count.store(0, Ordering::Relaxed);
+        self.request_started = Instant::now();
+        self.start_spinner(url);
+        let mut attempt = 0;
+        let result = loop {
+            let next = request.try_clone();
+            let result = match self.send_interruptible(request)? {
+                Some(result) => result,
+                None => {
+                    self.loading = None;
+                    return Err("Request cancelled".into());
+                }
+            };
+            let failure = match &result {
+                Ok(response) if response.status().is_server_error() => {
+                    Some(format!("HTTP {}", response.status()))
+                }
+                Err(e) if e.is_connect() => Some("Connection failed".to_string()),
+                _ => None,
+            };
+            match (failure, next) {
+                (Some(failure), Some(next)) if attempt < retries => {
+                    let delay = RETRY_BASE_DELAY.saturating_mul(2u32.saturating_pow(attempt));
+                    attempt += 1;
+                    self.loading = None;
+                    let message = format!(
+                        "{}, retrying in {:.1}s (attempt {}/{})",
+                        failure,
+                        delay.as_secs_f64(),
+                        attempt,
+                        retries
+                    );
+                    if self.headless {
+                        eprintln!("{}", message);
+                    } else {
+                        println!("{}", message);
+                    }
+                    if self
+                        .interruptible(move || std::thread::sleep(delay))?
+                        .is_none()
+                    {
+                        return Err("Request cancelled".into());
+                    }
+                    self.start_spinner(url);
+                    request = next;
+                }
+                _ => break result,
+            }
+        };
+        if result.is_err() {
+            self.loading = None;
+        }
+        let response = match result {
+            Ok(response) => response,
+            Err(e) if e.is_timeout() => {
+                return Err(format!(
+                    "Request to {} timed out after {} seconds",
+                    url,
+                    self.options.timeout.as_secs()
+                )
+                .into())
+            }
+            Err(e) if e.is_redirect() => {
+                return Err(format!(
+                    "Request to {} exceeded the redirect limit of {}",
+                    url, self.options.max_redirects
+                )
+                .into())
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let certificate = response
+            .extensions()
+            .get::<TlsInfo>()
+            .and_then(TlsInfo::peer_certificate)
+            .map(<[u8]>::to_vec);
+        let redirects = self.options.redirect_count.load(Ordering::Relaxed);
+        let tab = self.tab_mut();
+        tab.redirects = redirects;
+        tab.peer_certificate = certificate;
+        Ok(response)
+    }
+
+    fn begin_fetched_page(&mut self, url: &str, requested: &str) {
+        self.begin_page(url);
+        if self.tab().fragment.is_none() {
+            self.tab_mut().fragment = fragment_of(requested);
+        }
+    }
+
+    fn begin_page(&mut self, url: &str) {
+        let tab = self.tab_mut();
+        tab.current_url = Some(url.to_string());
+        tab.fragment = fragment_of(url);
+        tab.view_as = None;
+        tab.scroll_position = 0;
+        tab.search_pattern = None;
+        tab.search_matches.clear();
+        tab.from_cache = false;
+        tab.posted = false;
+    }
+
+    fn cache_take(&mut self, url: &str) -> Option<CacheEntry> {
+        let pos = self.cache.iter().position(|entry| entry.url == url)?;
+        self.cache.remove(pos)
+    }
+
+    fn cache_insert(&mut self, url: &str) {
+        self.cache_take(url);
+        self.cache.truncate(MAX_CACHE_ENTRIES.saturating_sub(1));
+        let tab = &self.tabs[self.active_tab];
+        self.cache.push_front(CacheEntry {
+            url: url.to_string(),
+            status: tab.status.unwrap_or(StatusCode::OK),
+            version: tab.version,
+            headers: tab.response_headers.clone(),
+            body: tab.body.clone(),
+        });
+    }
+
+    fn clear_cache(&mut self) {
+        self.cache.clear();
+        self.prefetcher.clear();
+    }
+
+    fn prefetch_visible(&mut self) {
+        if !self.config.prefetch || self.config.doh_url.is_some() {
+            return;
+        }
+        let links = match self.visible_links() {
+            Ok(links) => links,
+            Err(_) => return,
+        };
+        let delay = Duration::from_millis(self.config.host_delay_ms);
+        for (link, _, _) in links {
+            let url = match link
+                .checked_sub(1)
+                .and_then(|i| self.tab().links.get(i))
+                .and_then(|href| self.resolve_link(href))
+                .and_then(|url| Url::parse(&url).ok())
+            {
+                Some(url) if url.scheme() == "http" || url.scheme() == "https" => url,
+                _ => continue,
+            };
+            let host = match url.host_str() {
+                Some(host) => host.to_string(),
+                None => continue,
+            };
+            let throttled = self
+                .last_requests
+                .get(&host)
+                .is_some_and(|last| last.elapsed() < delay);
+            let href = url.to_string();
+            if throttled
+                || self.options.blocklist.blocks(&host)
+                || self.is_same_page(&href)
+                || !self.prefetch_safe(&url, &host)
+                || !self.prefetcher.wants(&href)
+            {
+                continue;
+            }
+            let request = self.negotiate(self.client.get(&href));
+            if !self
+                .prefetcher
+                .start(request, &href, self.config.prefetch_concurrency)
+            {
+                break;
+            }
+            if !delay.is_zero() {
+                self.last_requests.insert(host, Instant::now());
+            }
+        }
+    }
+
+    fn prefetch_safe(&self, url: &Url, host: &str) -> bool {
+        if self.credentials.contains_key(host) {
+            return false;
+        }
+        let has_cookies = self
+            .options
+            .cookies
+            .lock()
+            .map_or(true, |cookies| !cookies.matches(url).is_empty());
+        if has_cookies {
+            return false;
+        }
+        !self.config.respect_robots
+            || self
+                .robots
+                .get(&url.origin().ascii_serialization())
+                .is_some_and(|rules| rules.is_allowed(url.path()))
+    }
+
+    fn post(&mut self, request: PostRequest) -> Result<(), Box<dyn std::error::Error>> {
+        let url = Self::normalize_url(&request.url);
+        self.last_response = None;
+        let result = self.send_post(&url, request);
+        self.log_request("POST", &url, result.as_ref().err().map(|e| e.to_string()));
+        result
+    }
+
+    fn send_post(
+        &mut self,
+        url: &str,
+        request: PostRequest,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.resolve_via_doh(url)?;
+        let builder = self
+            .negotiate(self.authorize(self.client.post(url), url))
+            .header(CONTENT_TYPE, &request.content_type)
+            .body(request.body.clone());
+        self.tab_mut().last_post = Some(request);
+        let response = self.send(builder, url, 0)?;
+        self.handle_response(response, url)?;
+        self.tab_mut().posted = true;
+        Ok(())
+    }
+
+    fn log_request(&mut self, method: &str, url: &str, error: Option<String>) {
+        let response = self.last_response.take();
+        if !self.log_requests || self.private {
+            return;
+        }
+        let entry = match (error, response) {
+            (Some(e), _) => serde_json::json!({
+                "time": Utc::now().to_rfc3339(),
+                "method": method,
+                "url": url,
+                "error": e,
+            }),
+            (None, Some(response)) => serde_json::json!({
+                "time": Utc::now().to_rfc3339(),
+                "method": method,
+                "url": response.url,
+                "status": response.status.as_u16(),
+                "elapsed_ms": response.elapsed.as_millis() as u64,
+                "content_type": response.content_type,
+                "bytes": response.bytes,
+                "cached": response.cached,
+            }),
+            (None, None) => serde_json::json!({
+                "time": Utc::now().to_rfc3339(),
+                "method": method,
+                "url": url,
+            }),
+        };
+        let written = self
+            .open_state_file(
+                REQUEST_LOG_FILE,
+                OpenOptions::new().create(true).append(true),
+            )
+            .and_then(|mut file| writeln!(file, "{}", entry));
+        if let Err(e) = written {
+            eprintln!("Could not write {}: {}", REQUEST_LOG_FILE, e);
+        }
+    }
+
+    fn prompt_post(&mut self, url: &str) -> Result<(), Box<dyn std::error::Error>> {
+        print!("Content-Type [application/json]: ");
+        io::stdout().flush()?;
+        let mut content_type = String::new();
+        io::stdin().read_line(&mut content_type)?;
+        let content_type = match content_type.trim() {
+            "" => "application/json".to_string(),
+            value => value.to_string(),
+        };
+
+        println!("Body (finish with an empty line):");
+        let mut body = String::new();
+        loop {
+            let mut line = String::new();
+            if io::stdin().read_line(&mut line)? == 0 || line.trim().is_empty() {
+                break;
+            }
+            body.push_str(&line);
+        }
+
+        self.post(PostRequest {
+            url: url.to_string(),
+            content_type,
+            body: body.trim_end().to_string(),
+        })
+    }
+
+    fn resend(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        match self.tab().last_post.clone() {
+            Some(request) => self.post(request),
+            None => {
+                println!("No request to resend.");
+                Ok(())
+            }
+        }
+    }
+
+    fn perform(
+        &mut self,
+        action: Action,
+        argument: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match action {
+            Action::Quit => self.quitting = true,
+            Action::ScrollUp => self.scroll(-(self.config.scroll_step as isize))?,
+            Action::ScrollDown => self.scroll(self.config.scroll_step as isize)?,
+            Action::HalfPageUp => self.scroll(-Self::half_page()?)?,
+            Action::HalfPageDown => self.scroll(Self::half_page()?)?,
+            Action::Top => self.scroll_to_top()?,
+            Action::Bottom => self.scroll_to_bottom()?,
+            Action::NextMatch => self.next_match()?,
+            Action::PreviousMatch => self.previous_match()?,
+            Action::Reload => self.reload()?,
+            Action::HardReload => self.hard_reload()?,
+            Action::Back => self.back()?,
+            Action::Forward => self.forward()?,
+            Action::Search if argument.is_empty() => println!("Usage: search QUERY"),
+            Action::Search => self.search_in_page(argument)?,
+        }
+        Ok(())
+    }
+
+    fn reload(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if let (true, Some(request)) = (self.tab().posted, self.tab().last_post.clone()) {
+            print!("This page came from a form. Send it again? [y/N] ");
+            io::stdout().flush()?;
+            let mut answer = String::new();
+            io::stdin().read_line(&mut answer)?;
+            if answer.trim().eq_ignore_ascii_case("y") {
+                return self.post(request);
+            }
+            return Ok(self.display_page()?);
+        }
+        if let Some(url) = self.tab().current_url.clone() {
+            self.load(&url)?;
+        }
+        Ok(())
+    }
+
+    fn hard_reload(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(url) = self.tab().current_url.clone() {
+            self.cache_take(&url);
+            self.fetch(&url, true)?;
+        }
+        Ok(())
+    }
+
+    fn back(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let tab = self.tab();
+        let url = match tab.navigation.get(tab.navigation_cursor + 1) {
+            Some(url) => url.clone(),
+            None => {
+                println!("No previous page.");
+                return Ok(());
+            }
+        };
+        self.load(&url)?;
+        self.tab_mut().navigation_cursor += 1;
+        Ok(())
+    }
+
+    fn forward(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let tab = self.tab();
+        let url = match tab.navigation_cursor.checked_sub(1) {
+            Some(i) => tab.navigation[i].clone(),
+            None => {
+                println!("No next page.");
+                return Ok(());
+            }
+        };
+        self.load(&url)?;
+        self.tab_mut().navigation_cursor -= 1;
+        Ok(())
+    }
+
+    fn handle_response(
+        &mut self,
+        response: Response,
+        requested: &str,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let final_url = response.url().to_string();
+        let status = response.status();
+        let version = response.version();
+        let headers = response.headers().clone();
+        let limit = self.config.max_body_bytes;
+        let body =
+            self.interruptible(move || read_limited(response, limit).map_err(|e| e.to_string()));
+        let elapsed = self.request_started.elapsed();
+        self.loading = None;
+        let body = match body? {
+            Some(body) => body?,
+            None => return Err("Request cancelled".into()),
+        };
+        self.last_response = Some(LoggedResponse::new(
+            &final_url,
+            status,
+            &headers,
+            body.len(),
+            elapsed,
+            false,
+        ));
+
+        self.begin_fetched_page(&final_url, requested);
+        let tab = self.tab_mut();
+        tab.status = Some(status);
+        tab.version = version;
+        tab.response_headers = headers;
+        tab.body = body;
+        tab.elapsed = Some(elapsed);
+        self.render_body()?;
+        Ok(final_url)
+    }
+
+    fn confirm_large_page(&self) -> io::Result<bool> {
+        let size = self.tab().body.len() as u64;
+        let threshold = self.config.large_page_bytes;
+        if self.headless || threshold == 0 || size <= threshold {
+            return Ok(true);
+        }
+
+        print!("Page is {} - render anyway? [y/N] ", format_size(size));
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if answer.trim().eq_ignore_ascii_case("y") {
+            return Ok(true);
+        }
+
+        print!("Save it to a file instead? Filename (empty to skip): ");
+        io::stdout().flush()?;
+        let mut filename = String::new();
+        io::stdin().read_line(&mut filename)?;
+        let filename = filename.trim();
+        if !filename.is_empty() {
+            match std::fs::write(filename, &self.tab().body) {
+                Ok(()) => println!("Saved to {}", filename),
+                Err(e) => toast::show(&format!("Error: {}", e), Color::DarkRed)?,
+            }
+        }
+        Ok(false)
+    }
+
+    fn render_body(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let content_type = match self.tab().view_as {
+            Some(content_type) => content_type.to_string(),
+            None => self
+                .tab()
+                .response_headers
+                .get(CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("")
+                .to_string(),
+        };
+
+        self.loading = None;
+        let tab = self.tab_mut();
+        tab.title = None;
+        tab.raw_html = None;
+        tab.article_html = None;
+        tab.reader_mode = false;
+        tab.links.clear();
+        tab.next_page = None;
+        tab.previous_page = None;
+        tab.is_feed = false;
+        tab.is_image = false;
+        tab.is_binary = false;
+        tab.is_xml = false;
+        tab.json_tree = None;
+        tab.json_pretty = false;
+        if !content_type.starts_with("image/") && !self.confirm_large_page()? {
+            let size = format_size(self.tab().body.len() as u64);
+            self.tab_mut().page_content = format!(
+                "Rendering skipped: the page is {}. Use download FILE to save it or r to try again.",
+                size
+            );
+        } else if self.tab().body.is_empty() {
+            self.tab_mut().page_content = String::new();
+        } else if content_type.contains("rss") || content_type.contains("atom") {
+            let feed = feed_rs::parser::parse(self.tab().body.as_slice())?;
+            let width = self.target_width();
+            let tab = self.tab_mut();
+            (tab.page_content, tab.links) = Self::render_feed(&feed, width);
+            let tab = self.tab_mut();
+            tab.is_feed = true;
+            tab.title = feed
+                .title
+                .as_ref()
+                .map(|title| title.content.trim().to_string());
+        } else if content_type.contains("text/html") {
+            let html = Self::decode_body(&self.tab().body, &content_type);
+            let tab = self.tab_mut();
+            tab.title = Self::html_title(&html);
+            tab.next_page = Self::pagination_link(&html, NEXT_LABELS);
+            tab.previous_page = Self::pagination_link(&html, PREVIOUS_LABELS);
+            tab.raw_html = Some(html);
+            self.render_stored_html();
+        } else if content_type.starts_with("image/") {
+            self.tab_mut().is_image = true;
+            self.render_stored_image()?;
+        } else if Self::is_binary(&self.tab().body, &content_type) {
+            let tab = self.tab_mut();
+            tab.is_binary = true;
+            tab.page_content = format!(
+                "Binary content ({}) - use download FILE to save it",
+                format_size(tab.body.len() as u64)
+            );
+        } else if content_type.contains("application/json") {
+            let tree = JsonTree::new(serde_json::from_slice(&self.tab().body)?);
+            let tab = self.tab_mut();
+            tab.page_content = entities::decode(&tree.render()).into_owned();
+            tab.json_tree = Some(tree);
+        } else if content_type.contains("xml") {
+            let source = Self::decode_body(&self.tab().body, &content_type);
+            let tab = self.tab_mut();
+            tab.page_content = xml::pretty(&source);
+            tab.is_xml = true;
+        } else if content_type.contains("text/markdown") {
+            let text = Self::decode_body(&self.tab().body, &content_type);
+            self.tab_mut().page_content = Self::render_markdown(&text);
+        } else if content_type.contains("text/plain") {
+            let text = Self::decode_body(&self.tab().body, &content_type);
+            self.tab_mut().page_content = entities::decode(&text).into_owned();
+        } else {
+            self.tab_mut().page_content =
+                format!("Content-Type '{}' not supported for display", content_type);
+        }
+        self.finish_page_content();
+        if let Some(fragment) = self.tab_mut().fragment.take() {
+            if let Some(line) = self.anchor_line(&fragment) {
+                self.tab_mut().scroll_position = line.min(self.max_scroll()?);
+            }
+        }
+
+        self.display_page()?;
+        Ok(())
+    }
+
+    fn view_as(&mut self, kind: &str) -> Result<(), Box<dyn std::error::Error>> {
+        if self.tab().current_url.is_none() {
+            println!("No page loaded.");
+            return Ok(());
+        }
+        let content_type = match VIEW_AS_TYPES.iter().find(|(name, _)| *name == kind) {
+            Some((_, content_type)) => Some(*content_type),
+            None if kind == "auto" => None,
+            None => {
+                println!("Usage: view-as html|json|text|xml|markdown|auto");
+                return Ok(());
+            }
+        };
+        self.tab_mut().view_as = content_type;
+        self.tab_mut().scroll_position = 0;
+        self.render_body()
+    }
+
+    fn anchor_line(&self, fragment: &str) -> Option<usize> {
+        let html = self.tab().raw_html.as_ref()?;
+        let text = dom::anchor_text(html, fragment)?;
+        let needle: Vec<&str> = text.split_whitespace().take(ANCHOR_MATCH_WORDS).collect();
+        let needle = needle.join(" ");
+        let lines: Vec<String> = self
+            .tab()
+            .page_content
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect();
+        lines
+            .iter()
+            .position(|line| line.starts_with('#') && line.contains(&needle))
+            .or_else(|| lines.iter().position(|line| line.contains(&needle)))
+    }
+
+    fn jump_to_fragment(&mut self, url: &str) -> io::Result<()> {
+        let fragment = fragment_of(url).unwrap_or_default();
+        match self.anchor_line(&fragment) {
+            Some(line) => {
+                self.tab_mut().current_url = Some(url.to_string());
+                self.go_to_line(line + 1)
+            }
+            None => {
+                println!("No anchor #{} on this page.", fragment);
+                Ok(())
+            }
+        }
+    }
+
+    fn is_same_page(&self, url: &str) -> bool {
+        let strip = |url: &str| {
+            Url::parse(url).ok().map(|mut url| {
+                url.set_fragment(None);
+                url
+            })
+        };
+        let current = match &self.tab().current_url {
+            Some(current) => strip(current),
+            None => return false,
+        };
+        fragment_of(url).is_some() && self.tab().raw_html.is_some() && strip(url) == current
+    }
+
+    fn finish_page_content(&mut self) {
+        let width = self.target_width();
+        let tab = self.tab_mut();
+        if tab
+            .page_content
+            .lines()
+            .any(|line| line.chars().count() > width)
+        {
+            tab.page_content = hard_wrap(&tab.page_content, width);
+        }
+
+        let status = match self.tab().status {
+            Some(status) => status,
+            None => return,
+        };
+
+        let tab = self.tab_mut();
+        if tab.page_content.trim().is_empty() {
+            tab.page_content = format!("The server returned HTTP {} with no content.", status);
+        }
+        if !status.is_success() {
+            tab.page_content = format!("HTTP {}\n\n{}", status, tab.page_content);
+        }
+    }
+
+    fn is_binary(bytes: &[u8], content_type: &str) -> bool {
+        let textual = content_type.is_empty()
+            || content_type.starts_with("text/")
+            || ["json", "xml", "javascript"]
+                .iter()
+                .any(|kind| content_type.contains(kind));
+        if !textual {
+            return true;
+        }
+        let sample = &bytes[..bytes.len().min(1024)];
+        let control = sample
+            .iter()
+            .filter(|&&b| b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0c | 0x1b))
+            .count();
+        sample.contains(&0) || control * 10 > sample.len()
+    }
+
+    fn decode_body(bytes: &[u8], content_type: &str) -> String {
+        let encoding = Self::charset_param(content_type)
+            .or_else(|| Self::meta_charset(bytes))
+            .and_then(|label| Encoding::for_label(label.as_bytes()))
+            .unwrap_or(UTF_8);
+        let (text, _, _) = encoding.decode(bytes);
+        text.into_owned()
+    }
+
+    fn charset_param(value: &str) -> Option<String> {
+        value.split(';').find_map(|param| {
+            let (key, charset) = param.split_once('=')?;
+            if key.trim().eq_ignore_ascii_case("charset") {
+                Some(charset.trim().trim_matches(['"', '\'']).to_string())
+            } else {
+                None
+            }
+        })
+    }
+
+    fn meta_charset(bytes: &[u8]) -> Option<String> {
+        let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]).to_lowercase();
+        let start = head.find("charset=")? + "charset=".len();
+        let charset: String = head[start..]
+            .trim_start_matches(['"', '\''])
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+            .collect();
+        if charset.is_empty() {
+            None
+        } else {
+            Some(charset)
+        }
+    }
+
+    fn html_title(html: &str) -> Option<String> {
+        let document = dom::parse(html).document;
+        let title = dom::find_all(&document, "title").into_iter().next()?;
+        let title = dom::text_of(&title)
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if title.is_empty() {
+            None
+        } else {
+            Some(title)
+        }
+    }
+
+    fn pagination_link(html: &str, labels: &[&str]) -> Option<String> {
+        let document = dom::parse(html).document;
+        let has_rel = |node: &markup5ever_rcdom::Handle| {
+            dom::attr(node, "rel").is_some_and(|rel| {
+                rel.split_whitespace()
+                    .any(|rel| labels.contains(&rel.to_lowercase().as_str()))
+            })
+        };
+
+        let links = dom::find_all(&document, "link").into_iter();
+        let anchors = dom::find_all(&document, "a");
+        links
+            .chain(anchors.iter().cloned())
+            .filter(|node| has_rel(node))
+            .find_map(|node| dom::attr(&node, "href"))
+            .or_else(|| {
+                anchors.iter().find_map(|anchor| {
+                    let text = dom::text_of(anchor)
+                        .trim_matches(|c: char| !c.is_alphanumeric())
+                        .to_lowercase();
+                    if labels.contains(&text.as_str()) {
+                        dom::attr(anchor, "href")
+                    } else {
+                        None
+                    }
+                })
+            })
+    }
+
+    fn follow_pagination(&mut self, next: bool) -> Result<(), Box<dyn std::error::Error>> {
+        let tab = self.tab();
+        let href = if next {
+            &tab.next_page
+        } else {
+            &tab.previous_page
+        };
+        let url = match href.as_deref().and_then(|href| self.resolve_link(href)) {
+            Some(url) => url,
+            None => {
+                println!("No {} page found.", if next { "next" } else { "previous" });
+                return Ok(());
+            }
+        };
+        self.navigate(&url)
+    }
+
+    fn render_stored_html(&mut self) {
+        let width = self.target_width();
+        let tab = self.tab_mut();
+        let html = match (&tab.article_html, &tab.raw_html) {
+            (Some(article), _) if tab.reader_mode => article,
+            (_, Some(html)) => html,
+            _ => return,
+        };
+        (tab.page_content, tab.links) = Self::render_html(html, width);
+        tab.render_width = width;
+    }
+
+    fn render_stored_image(&mut self) -> io::Result<()> {
+        let width = self.target_width();
+        let height = Self::viewport_height()?;
+        let tab = self.tab_mut();
+        tab.page_content = match picture::render(&tab.body, width, height) {
+            Ok(picture) => picture,
+            Err(e) => format!("Could not decode image: {}", e),
+        };
+        tab.render_width = width;
+        Ok(())
+    }
+
+    fn toggle_reader_mode(&mut self) -> io::Result<()> {
+        let html = match &self.tab().raw_html {
+            Some(html) => html,
+            None => {
+                println!("Reader mode is only available for HTML pages.");
+                return Ok(());
+            }
+        };
+        if self.tab().article_html.is_none() {
+            self.tab_mut().article_html = reader::extract(html);
+        }
+        if self.tab().article_html.is_none() {
+            println!("Could not find an article on this page.");
+            return Ok(());
+        }
+
+        let tab = self.tab_mut();
+        tab.reader_mode = !tab.reader_mode;
+        tab.scroll_position = 0;
+        tab.search_matches.clear();
+        self.render_stored_html();
+        self.finish_page_content();
+        self.display_page()
+    }
+
+    fn render_html(html: &str, width: usize) -> (String, Vec<String>) {
+        let decorator = LinkDecorator::new();
+        let links = decorator.links();
+        let html = table::align(html, width);
+        let text = html2text::from_read_with_decorator(html.as_bytes(), width, decorator);
+        let links = links.take();
+        (text, links)
+    }
+
+    fn render_feed(feed: &Feed, width: usize) -> (String, Vec<String>) {
+        let mut lines = Vec::new();
+        let mut links = Vec::new();
+
+        let title = feed.title.as_ref().map(|t| t.content.as_str());
+        lines.push(format!("# {}", title.unwrap_or("Untitled feed")));
+        if let Some(description) = &feed.description {
+            lines.push(description.content.trim().to_string());
+        }
+
+        for entry in &feed.entries {
+            let title = entry.title.as_ref().map(|t| t.content.trim());
+            let mut heading = format!("## {}", title.unwrap_or("Untitled entry"));
+            if let Some(link) = entry.links.first() {
+                links.push(link.href.clone());
+                heading.push_str(&format!("[{}]", links.len()));
+            }
+
+            lines.push(String::new());
+            lines.push(heading);
+            if let Some(date) = entry.published.or(entry.updated) {
+                let date: DateTime<Local> = date.into();
+                lines.push(date.format("%Y-%m-%d %H:%M").to_string());
+            }
+            if let Some(summary) = &entry.summary {
+                let summary = html2text::from_read_with_decorator(
+                    summary.content.as_bytes(),
+                    width,
+                    TrivialDecorator::new(),
+                );
+                lines.extend(summary.lines().map(String::from));
+            }
+        }
+
+        (lines.join("\n"), links)
+    }
+
+    fn render_markdown(markdown: &str) -> String {
+        let lines: Vec<&str> = markdown.lines().collect();
+        let mut rendered = Vec::with_capacity(lines.len());
+        let mut i = 0;
+
+        while i < lines.len() {
+            let line = lines[i];
+            let underline = lines.get(i + 1).map(|l| l.trim()).unwrap_or("");
+            let level = if underline.len() >= 2 && underline.chars().all(|c| c == '=') {
+                1
+            } else if underline.len() >= 2 && underline.chars().all(|c| c == '-') {
+                2
+            } else {
+                0
+            };
+
+            if level > 0 && !line.trim().is_empty() {
+                rendered.push(format!("{} {}", "#".repeat(level), line.trim()));
+                i += 2;
+            } else {
+                rendered.push(line.to_string());
+                i += 1;
+            }
+        }
+
+        rendered.join("\n")
+    }
+
+    fn resolve_link(&self, href: &str) -> Option<String> {
+        match &self.tab().current_url {
+            Some(base) => Url::parse(base).ok()?.join(href).ok().map(String::from),
+            None => Url::parse(href).ok().map(String::from),
+        }
+    }
+
+    fn follow_link(&mut self, index: usize) -> Result<(), Box<dyn std::error::Error>> {
+        let href = match index.checked_sub(1).and_then(|i| self.tab().links.get(i)) {
+            Some(href) => href,
+            None => {
+                println!("No link numbered {}.", index);
+                return Ok(());
+            }
+        };
+        match self.resolve_link(href) {
+            Some(url) if self.is_same_page(&url) => Ok(self.jump_to_fragment(&url)?),
+            Some(url) => self.navigate(&url),
+            None => {
+                println!("Cannot resolve link: {}", href);
+                Ok(())
+            }
+        }
+    }
+
+    fn open_externally(&self) {
+        match &self.tab().current_url {
+            Some(url) => match webbrowser::open(url) {
+                Ok(()) => println!("Opened {} in the default browser", url),
+                Err(e) => println!("Could not open the default browser: {}", e),
+            },
+            None => println!("No page loaded."),
+        }
+    }
+
+    fn yank(&mut self, index: Option<usize>) {
+        let url = match index {
+            None => self.tab().current_url.clone(),
+            Some(index) => index
+                .checked_sub(1)
+                .and_then(|i| self.tab().links.get(i))
+                .and_then(|href| self.resolve_link(href)),
+        };
+        let url = match (url, index) {
+            (Some(url), _) => url,
+            (None, Some(index)) => {
+                println!("No link numbered {}.", index);
+                return;
+            }
+            (None, None) => {
+                println!("No page loaded.");
+                return;
+            }
+        };
+
+        if self.clipboard.is_none() {
+            self.clipboard = arboard::Clipboard::new().ok();
+        }
+        match self
+            .clipboard
+            .as_mut()
+            .map(|clipboard| clipboard.set_text(url.clone()))
+        {
+            Some(Ok(())) => println!("Copied {}", url),
+            _ => println!("Clipboard unavailable, URL: {}", url),
+        }
+    }
+
+    fn target_width(&self) -> usize {
+        if let Some(width) = self.config.render_width {
+            return width;
+        }
+        crossterm::terminal::size()
+            .map(|(width, _)| (width as usize).saturating_sub(self.gutter_width()).max(1))
+            .unwrap_or(DEFAULT_RENDER_WIDTH)
+    }
+
+    fn gutter_width(&self) -> usize {
+        gutter_width(self.tab().page_content.lines().count(), self.show_numbers)
+    }
+
+    fn viewport_height() -> io::Result<usize> {
+        Ok((crossterm::terminal::size()?.1 as usize)
+            .saturating_sub(7)
+            .max(1))
+    }
+
+    fn visual_rows(&self) -> io::Result<Vec<usize>> {
+        let width = crossterm::terminal::size()?.0 as usize;
+        let gutter = self.gutter_width();
+        Ok(self
+            .tab()
+            .page_content
+            .lines()
+            .map(|line| line_rows(line, width, gutter))
+            .collect())
+    }
+
+    fn max_scroll(&self) -> io::Result<usize> {
+        let rows = self.visual_rows()?;
+        let height = Self::viewport_height()?;
+        let mut used = 0;
+        let mut position = rows.len();
+        while position > 0 && used + rows[position - 1] <= height {
+            position -= 1;
+            used += rows[position];
+        }
+        Ok(position.min(rows.len().saturating_sub(1)))
+    }
+
+    fn scroll(&mut self, delta: isize) -> io::Result<()> {
+        if !self.config.smooth_scroll || self.headless || !io::stdout().is_terminal() {
+            return self.scroll_by(delta);
+        }
+        let start = self.tab().scroll_position.min(self.max_scroll()?);
+        let target = self.scroll_target(delta)?;
+        let distance = target as isize - start as isize;
+        let mut shown = start;
+        for frame in 1..=SMOOTH_SCROLL_FRAMES {
+            let position = (start as isize + distance * frame / SMOOTH_SCROLL_FRAMES) as usize;
+            if position == shown {
+                continue;
+            }
+            if shown != start {
+                std::thread::sleep(SMOOTH_SCROLL_FRAME_TIME);
+            }
+            self.tab_mut().scroll_position = position;
+            self.display_page()?;
+            shown = position;
+        }
+        if shown == start {
+            self.display_page()?;
+        }
+        Ok(())
+    }
+
+    fn set_scroll_step(&mut self, step: &str) {
+        match step.parse::<usize>() {
+            Ok(step) if step > 0 => {
+                self.config.scroll_step = step;
+                println!("Scrolling {} lines at a time.", step);
+            }
+            _ => println!("Usage: scroll-step N (a positive number of lines)"),
+        }
+    }
+
+    fn toggle_smooth_scroll(&mut self) {
+        self.config.smooth_scroll = !self.config.smooth_scroll;
+        println!(
+            "Smooth scrolling {}.",
+            if self.config.smooth_scroll {
+                "on"
+            } else {
+                "off"
+            }
+        );
+    }
+
+    fn scroll_by(&mut self, delta: isize) -> io::Result<()> {
+        self.tab_mut().scroll_position = self.scroll_target(delta)?;
+        self.display_page()
+    }
+
+    fn scroll_target(&self, delta: isize) -> io::Result<usize> {
+        let rows = self.visual_rows()?;
+        let max_scroll = self.max_scroll()?;
+        let mut position = self.tab().scroll_position.min(max_scroll);
+        let mut moved = 0;
+        if delta > 0 {
+            while moved < delta.unsigned_abs() && position < max_scroll {
+                moved += rows[position];
+                position += 1;
+            }
+        } else {
+            while moved < delta.unsigned_abs() && position > 0 {
+                position -= 1;
+                moved += rows[position];
+            }
+        }
+        Ok(position)
+    }
+
+    fn scroll_to_top(&mut self) -> io::Result<()> {
+        self.tab_mut().scroll_position = 0;
+        self.display_page()
+    }
+
+    fn scroll_to_bottom(&mut self) -> io::Result<()> {
+        self.tab_mut().scroll_position = self.max_scroll()?;
+        self.display_page()
+    }
+
+    fn click(&mut self, column: usize, row: usize) -> Result<(), Box<dyn std::error::Error>> {
+        let (rows, index) = match self
+            .screen_lines
+            .iter()
+            .find(|(rows, _)| rows.contains(&row))
+        {
+            Some((rows, index)) => (rows.clone(), *index),
+            None => return Ok(self.display_page()?),
+        };
+        let width = crossterm::terminal::size()?.0 as usize;
+        let offset = ((row - rows.start) * width + column).saturating_sub(self.gutter_width());
+
+        let line = self.tab().page_content.lines().nth(index).unwrap_or("");
+        match link_at(line, offset) {
+            Some(link) => self.follow_link(link),
+            None => Ok(self.display_page()?),
+        }
+    }
+
+    fn visible_links(&self) -> io::Result<Vec<(usize, u16, u16)>> {
+        let width = crossterm::terminal::size()?.0 as usize;
+        let lines: Vec<&str> = self.tab().page_content.lines().collect();
+        let gutter = self.gutter_width();
+        let mut links = Vec::new();
+        for (rows, index) in &self.screen_lines {
+            let line = lines.get(*index).copied().unwrap_or("");
+            for (end, link) in link_markers(line) {
+                if link == 0 || link > self.tab().links.len() {
+                    continue;
+                }
+                let offset = gutter + end - link.to_string().len() - 2;
+                let row = rows.start + offset / width.max(1);
+                if rows.contains(&row) && !links.iter().any(|(seen, _, _)| *seen == link) {
+                    links.push((link, (offset % width.max(1)) as u16, row as u16));
+                }
+            }
+        }
+        Ok(links)
+    }
+
+    fn follow_hint(&mut self, new_tab: bool) -> Result<(), Box<dyn std::error::Error>> {
+        let links = self.visible_links()?;
+        if links.is_empty() {
+            println!("No links on screen.");
+            return Ok(());
+        }
+        let labels = hints::labels(links.len());
+
+        let mut typed = String::new();
+        let picked = loop {
+            self.display_page()?;
+            for ((_, column, row), label) in links.iter().zip(&labels) {
+                if !label.starts_with(&typed) {
+                    continue;
+                }
+                execute!(
+                    io::stdout(),
+                    cursor::MoveTo(*column, *row),
+                    SetBackgroundColor(Color::Yellow),
+                    SetForegroundColor(Color::Black)
+                )?;
+                print!("{}", label);
+                execute!(io::stdout(), ResetColor)?;
+            }
+            execute!(
+                io::stdout(),
+                cursor::MoveTo(0, (Self::viewport_height()? + 6) as u16)
+            )?;
+            print!("Hint: {}", typed);
+            io::stdout().flush()?;
+
+            if !io::stdin().is_terminal() {
+                let mut input = String::new();
+                io::stdin().read_line(&mut input)?;
+                typed = input.trim().to_string();
+            } else {
+                enable_raw_mode()?;
+                let event = read();
+                disable_raw_mode()?;
+                match event? {
+                    Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                        KeyCode::Esc => break None,
+                        KeyCode::Backspace => {
+                            typed.pop();
+                        }
+                        KeyCode::Char(c) => typed.push(c.to_ascii_lowercase()),
+                        _ => {}
+                    },
+                    _ => continue,
+                }
+            }
+
+            if let Some(i) = labels.iter().position(|label| *label == typed) {
+                break Some(links[i].0);
+            }
+            if !labels.iter().any(|label| label.starts_with(&typed)) {
+                break None;
+            }
+        };
+
+        match picked {
+            Some(link) if new_tab => {
+                let href = match link.checked_sub(1).and_then(|i| self.tab().links.get(i)) {
+                    Some(href) => href.clone(),
+                    None => {
+                        println!("No link numbered {}.", link);
+                        return Ok(());
+                    }
+                };
+                match self.resolve_link(&href) {
+                    Some(url) => self.new_tab(Some(&url)),
+                    None => {
+                        println!("Cannot resolve link: {}", href);
+                        Ok(())
+                    }
+                }
+            }
+            Some(link) => self.follow_link(link),
+            None => Ok(self.display_page()?),
+        }
+    }
+
+    fn report_error(&mut self, error: &dyn std::fmt::Display) -> io::Result<()> {
+        toast::show(&format!("Error: {}", error), Color::DarkRed)?;
+        if io::stdin().is_terminal() {
+            self.display_page()?;
+        }
+        Ok(())
+    }
+
+    fn go_to_line(&mut self, line: usize) -> io::Result<()> {
+        let position = line.saturating_sub(1).min(self.max_scroll()?);
+        self.tab_mut().scroll_position = position;
+        self.display_page()
+    }
+
+    fn half_page() -> io::Result<isize> {
+        Ok((Self::viewport_height()? / 2).max(1) as isize)
+    }
+
+    fn display_page(&mut self) -> io::Result<()> {
+        if self.headless {
+            return Ok(());
+        }
+        let width = self.target_width();
+        if width != self.tab().render_width {
+            if self.tab().raw_html.is_some() {
+                self.render_stored_html();
+                self.finish_page_content();
+            } else if self.tab().is_image {
+                self.render_stored_image()?;
+                self.finish_page_content();
+            }
+        }
+
+        execute!(io::stdout(), Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+
+        execute!(
+            io::stdout(),
+            SetBackgroundColor(self.theme.header_bg),
+            SetForegroundColor(self.theme.header_fg)
+        )?;
+
+        let terminal_width = crossterm::terminal::size()?.0 as usize;
+        let mut header = match &self.tab().title {
+            Some(title) => format!(" {} - Rust Web Browser ", title),
+            None => " Rust Web Browser ".to_string(),
+        };
+        if self.tab().is_feed {
+            header.push_str("[feed] ");
+        }
+        if self.tab().is_image {
+            header.push_str("[image] ");
+        }
+        if self.tab().is_binary {
+            header.push_str("[binary] ");
+        }
+        if self.private {
+            header.push_str("[private] ");
+        }
+        if self.options.accept_invalid_certs {
+            header.push_str("[insecure] ");
+        }
+        if self.tab().reader_mode {
+            header.push_str("[reader] ");
+        }
+        if self.tabs.len() > 1 {
+            header.push_str(&self.tab_strip());
+        }
+        println!("{}", fit_to_width(&header, terminal_width));
+
+        execute!(
+            io::stdout(),
+            ResetColor,
+            SetBackgroundColor(self.theme.url_bg),
+            SetForegroundColor(self.theme.url)
+        )?;
+
+        let url = self.tab().current_url.as_deref().unwrap_or("No URL");
+        println!("└─ URL: {}\n", url);
+        let url_rows = ("└─ URL: ".chars().count() + url.chars().count())
+            .div_ceil(terminal_width.max(1))
+            .max(1);
+
+        execute!(io::stdout(), ResetColor)?;
+
+        let lines: Vec<&str> = self.tab().page_content.lines().collect();
+        let terminal_height = Self::viewport_height()?;
+        let max_scroll = self.max_scroll()?;
+
+        let effective_scroll = std::cmp::min(self.tab().scroll_position, max_scroll);
+        let current_match = self.tab().search_pattern.as_ref().and_then(|_| {
+            self.tab()
+                .search_matches
+                .get(self.tab().search_index)
+                .copied()
+        });
+        let visible_lines = lines.get(effective_scroll..).unwrap_or_default();
+
+        let mut used_rows = 0;
+        let mut screen_lines = Vec::new();
+        let mut markup = Highlighter::new(Language::Markup);
+        let gutter = self.gutter_width();
+        for (i, line) in visible_lines.iter().enumerate() {
+            let rows = line_rows(line, terminal_width, gutter);
+            if i > 0 && used_rows + rows > terminal_height {
+                break;
+            }
+            let first_row = 2 + url_rows + used_rows;
+            screen_lines.push((first_row..first_row + rows, i + effective_scroll));
+            used_rows += rows;
+            let is_banner = i + effective_scroll == 0
+                && self.tab().status.is_some_and(|status| !status.is_success());
+            let color = if is_banner {
+                self.theme.error
+            } else if line.trim().starts_with('#') {
+                self.theme.heading
+            } else if line.contains("http") || line.contains("www.") {
+                self.theme.link
+            } else {
+                self.theme.text
+            };
+            execute!(io::stdout(), SetForegroundColor(color))?;
+            if gutter > 0 {
+                let digits = gutter - GUTTER_SEPARATOR.chars().count();
+                if current_match == Some(i + effective_scroll) {
+                    execute!(
+                        io::stdout(),
+                        SetBackgroundColor(CURRENT_MATCH_COLOR),
+                        SetForegroundColor(Color::Black)
+                    )?;
+                    print!("{:>digits$}", i + effective_scroll + 1);
+                    execute!(io::stdout(), ResetColor, SetForegroundColor(color))?;
+                    print!("{}", GUTTER_SEPARATOR);
+                } else {
+                    print!("{:>digits$}{}", i + effective_scroll + 1, GUTTER_SEPARATOR);
+                }
+            }
+            match &self.tab().search_pattern {
+                Some(pattern) => {
+                    let background = if current_match == Some(i + effective_scroll) {
+                        CURRENT_MATCH_COLOR
+                    } else {
+                        Color::Yellow
+                    };
+                    Self::print_highlighted(line, pattern, color, background)?
+                }
+                None if self.tab().is_xml && !is_banner => {
+                    for (color, text) in markup.highlight(line) {
+                        execute!(io::stdout(), SetForegroundColor(color))?;
+                        print!("{}", text);
+                    }
+                }
+                None => print!("{}", line),
+            }
+            println!();
+            execute!(io::stdout(), ResetColor)?;
+        }
+
+        execute!(
+            io::stdout(),
+            cursor::MoveTo(0, (terminal_height + 5) as u16),
+            SetBackgroundColor(self.theme.status_bg),
+            SetForegroundColor(self.theme.status_fg)
+        )?;
+
+        let rows = self.visual_rows()?;
+        let rows_above: usize = rows.iter().take(effective_scroll).sum();
+        let page_count = rows.iter().sum::<usize>().div_ceil(terminal_height).max(1);
+        let mut status = format!(
+            " Lines: {} | Position: {} | {} | Page {}/{} ",
+            lines.len(),
+            effective_scroll + 1,
+            scroll_label(effective_scroll, max_scroll),
+            if effective_scroll >= max_scroll {
+                page_count
+            } else {
+                (rows_above / terminal_height + 1).min(page_count)
+            },
+            page_count
+        );
+        if let Some(code) = self.tab().status {
+            status.push_str(&format!("| HTTP {} ", code));
+        }
+        if let Some(elapsed) = self.tab().elapsed {
+            status.push_str(&format!(
+                "| {} | {} ",
+                format_elapsed(elapsed),
+                format_size(self.tab().body.len() as u64)
+            ));
+        }
+        if self.tab().from_cache {
+            status.push_str("| Cached ");
+        }
+        if let Some(accept) = self.accept.as_ref().and_then(|accept| accept.to_str().ok()) {
+            status.push_str(&format!("| Accept: {} ", accept));
+        }
+        if self.tab().redirects > 0 {
+            status.push_str(&format!("| Redirects: {} ", self.tab().redirects));
+        }
+        if self.tab().previous_page.is_some() {
+            status.push_str("| prev ");
+        }
+        if self.tab().next_page.is_some() {
+            status.push_str("| next ");
+        }
+        if !self.tab().search_matches.is_empty() {
+            status.push_str(&format!(
+                "| Match {}/{} ",
+                self.tab().search_index + 1,
+                self.tab().search_matches.len()
+            ));
+        }
+        println!("{}", fit_to_width(&status, terminal_width));
+
+        execute!(
+            io::stdout(),
+            ResetColor,
+            SetForegroundColor(self.theme.hint)
+        )?;
+        println!("\n[Press 'h' for help] [w/s to scroll] [q to quit]");
+        self.screen_lines = screen_lines;
+
+        io::stdout().flush()?;
+        self.prefetch_visible();
+        Ok(())
+    }
+
+    fn add_bookmark(&mut self, title: &str) -> io::Result<()> {
+        let (folder, title) = match title.split_once('/') {
+            Some((folder, title)) if !folder.trim().is_empty() => {
+                (Some(folder.trim().to_string()), title.trim())
+            }
+            _ => (None, title),
+        };
+
+        if let Some(url) = &self.tab().current_url {
+            let title = match title {
+                "" => self.tab().title.clone().unwrap_or_else(|| url.clone()),
+                title => title.to_string(),
+            };
+            self.bookmarks.push(Bookmark {
+                title,
+                url: url.clone(),
+                folder,
+                key: None,
+            });
+            self.save_bookmarks()?;
+        }
+        Ok(())
+    }
+
+    fn set_quickmark(&mut self, index: usize, key: Option<char>) -> io::Result<()> {
+        if let Some(key) = key {
+            for bookmark in self.bookmarks.iter_mut().filter(|b| b.key == Some(key)) {
+                bookmark.key = None;
+            }
+        }
+        self.bookmarks[index].key = key;
+        self.save_bookmarks()
+    }
+
+    fn mark_current_page(&mut self, key: char) -> io::Result<()> {
+        let url = match &self.tab().current_url {
+            Some(url) => url.clone(),
+            None => {
+                println!("No page loaded.");
+                return Ok(());
+            }
+        };
+        let index = match self
+            .bookmarks
+            .iter()
+            .position(|bookmark| bookmark.url == url)
+        {
+            Some(index) => index,
+            None => {
+                self.add_bookmark("")?;
+                self.bookmarks.len() - 1
+            }
+        };
+        self.set_quickmark(index, Some(key))?;
+        println!("'{} now opens {}", key, url);
+        Ok(())
+    }
+
+    fn jump_to_quickmark(&mut self, key: char) -> Result<(), Box<dyn std::error::Error>> {
+        match self
+            .bookmarks
+            .iter()
+            .find(|bookmark| bookmark.key == Some(key))
+        {
+            Some(bookmark) => {
+                let url = bookmark.url.clone();
+                self.navigate(&url)
+            }
+            None => {
+                println!(
+                    "No bookmark is marked '{}. Use mark {} to assign one.",
+                    key, key
+                );
+                Ok(())
+            }
+        }
+    }
+
+    fn edit_bookmark(&mut self, index: usize) -> io::Result<()> {
+        let bookmark = &self.bookmarks[index];
+        print!("Title [{}]: ", bookmark.title);
+        io::stdout().flush()?;
+        let mut title = String::new();
+        io::stdin().read_line(&mut title)?;
+
+        print!("URL [{}]: ", bookmark.url);
+        io::stdout().flush()?;
+        let mut url = String::new();
+        io::stdin().read_line(&mut url)?;
+
+        let bookmark = &mut self.bookmarks[index];
+        let title = title.trim();
+        if !title.is_empty() {
+            bookmark.title = title.to_string();
+        }
+        let url = url.trim();
+        if !url.is_empty() {
+            bookmark.url = Self::normalize_url(url);
+        }
+        self.save_bookmarks()?;
+        toast::show("Bookmark updated!", Color::DarkGreen)?;
+        Ok(())
+    }
+
+    fn export_bookmarks(&self, filename: &str) -> io::Result<()> {
+        std::fs::write(filename, netscape::export(&self.bookmarks))?;
+        println!(
+            "Exported {} bookmarks to {}",
+            self.bookmarks.len(),
+            filename
+        );
+        Ok(())
+    }
+
+    fn import_bookmarks(&mut self, filename: &str) -> io::Result<()> {
+        let html = std::fs::read_to_string(filename)?;
+        let mut imported = 0;
+
+        for bookmark in netscape::import(&html) {
+            if !self.bookmarks.iter().any(|b| b.url == bookmark.url) {
+                self.bookmarks.push(bookmark);
+                imported += 1;
+            }
+        }
+
+        self.save_bookmarks()?;
+        println!("Imported {} new bookmarks from {}", imported, filename);
+        Ok(())
+    }
+
+    fn clean_bookmarks(&mut self, check: bool) -> io::Result<()> {
+        let duplicates = remove_duplicates(&mut self.bookmarks);
+        for bookmark in &duplicates {
+            println!("Removed duplicate: {} ({})", bookmark.title, bookmark.url);
+        }
+        if !duplicates.is_empty() {
+            self.save_bookmarks()?;
+        }
+        println!("Removed {} duplicate bookmarks.", duplicates.len());
+        if !check {
+            return Ok(());
+        }
+
+        let mut dead = Vec::new();
+        for index in 0..self.bookmarks.len() {
+            let url = self.bookmarks[index].url.clone();
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                continue;
+            }
+            if self.check_blocklist(&url).is_err() {
+                println!("Skipping {} (on the blocklist)", url);
+                continue;
+            }
+            self.throttle(&url);
+            print!("Checking {}... ", url);
+            io::stdout().flush()?;
+            if let Err(e) = self.resolve_via_doh(&url) {
+                println!("unreachable ({})", e);
+                continue;
+            }
+            match self.client.head(&url).send() {
+                Ok(response) if DEAD_LINK_STATUSES.contains(&response.status()) => {
+                    println!("{}", response.status());
+                    dead.push(index);
+                }
+                Ok(response) => println!("{}", response.status()),
+                Err(e) => println!("unreachable ({})", e),
+            }
+        }
+
+        if dead.is_empty() {
+            println!("No dead links found.");
+            return Ok(());
+        }
+        println!("\nDead links:");
+        for &index in &dead {
+            println!(
+                "{} ({})",
+                self.bookmarks[index].title, self.bookmarks[index].url
+            );
+        }
+        print!("Delete {} dead bookmarks? [y/N] ", dead.len());
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if !answer.trim().eq_ignore_ascii_case("y") {
+            println!("Kept all bookmarks.");
+            return Ok(());
+        }
+
+        for &index in dead.iter().rev() {
+            self.bookmarks.remove(index);
+        }
+        self.save_bookmarks()?;
+        println!("Removed {} dead bookmarks.", dead.len());
+        Ok(())
+    }
+
+    fn bookmark_groups(
+        &self,
+        filter: &str,
+        sort: BookmarkSort,
+    ) -> BTreeMap<Option<&str>, Vec<usize>> {
+        let filter = filter.to_lowercase();
+        let mut groups: BTreeMap<Option<&str>, Vec<usize>> = BTreeMap::new();
+        for (i, bookmark) in self.bookmarks.iter().enumerate() {
+            if bookmark.title.to_lowercase().contains(&filter)
+                || bookmark.url.to_lowercase().contains(&filter)
+            {
+                groups
+                    .entry(bookmark.folder.as_deref())
+                    .or_default()
+                    .push(i);
+            }
+        }
+        for indices in groups.values_mut() {
+            match sort {
+                BookmarkSort::Added => {}
+                BookmarkSort::Title => {
+                    indices.sort_by_key(|&i| self.bookmarks[i].title.to_lowercase())
+                }
+                BookmarkSort::Url => indices.sort_by_key(|&i| self.bookmarks[i].url.to_lowercase()),
+            }
+        }
+        groups
     }
 
     fn show_bookmarks(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut collapsed: HashSet<String> = HashSet::new();
+        let mut filter = String::new();
+        let mut sort = BookmarkSort::Added;
+
+        loop {
+            execute!(io::stdout(), Clear(ClearType::All))?;
+
+            execute!(
+                io::stdout(),
+                SetBackgroundColor(Color::Magenta),
+                SetForegroundColor(Color::White)
+            )?;
+            print!(" Bookmarks (sorted by {}) ", sort.label());
+            if !filter.is_empty() {
+                print!("matching \"{}\" ", filter);
+            }
+            println!();
+            execute!(io::stdout(), ResetColor)?;
+            println!();
+
+            let groups = self.bookmark_groups(&filter, sort);
+            if groups.is_empty() && !filter.is_empty() {
+                execute!(io::stdout(), SetForegroundColor(Color::Red))?;
+                println!("No bookmarks match \"{}\".", filter);
+            }
+
+            let mut visible = Vec::new();
+            for (folder, indices) in groups {
+                if let Some(folder) = folder {
+                    let is_collapsed = collapsed.contains(folder);
+                    execute!(io::stdout(), SetForegroundColor(Color::Magenta))?;
+                    println!(
+                        "{} {} ({})",
+                        if is_collapsed { "▸" } else { "▾" },
+                        folder,
+                        indices.len()
+                    );
+                    if is_collapsed {
+                        continue;
+                    }
+                }
+
+                let indent = if folder.is_some() { "  " } else { "" };
+                for i in indices {
+                    let bookmark = &self.bookmarks[i];
+                    visible.push(i);
+
+                    execute!(io::stdout(), SetForegroundColor(Color::Yellow))?;
+                    print!("{} {}. ", indent, visible.len());
+
+                    if let Some(key) = bookmark.key {
+                        execute!(io::stdout(), SetForegroundColor(Color::Magenta))?;
+                        print!("'{} ", key);
+                    }
+
+                    execute!(io::stdout(), SetForegroundColor(Color::White))?;
+                    print!("{} ", bookmark.title);
+
+                    execute!(io::stdout(), SetForegroundColor(Color::Blue))?;
+                    println!("({})", bookmark.url);
+                }
+            }
+
+            execute!(io::stdout(), ResetColor)?;
+            println!("\nCommands:");
+            println!("number - Go to bookmark");
+            println!("d number - Delete bookmark");
+            println!("e number - Edit bookmark title and URL");
+            println!("k number [letter] - Set or clear the quickmark letter");
+            println!("f folder - Collapse or expand folder");
+            println!("/query - Show only matching bookmarks (/ alone clears)");
+            println!("sort - Cycle sorting by title, URL and date added");
+            println!("q - Return to browser");
+
+            print!("\nEnter command: ");
+            io::stdout().flush()?;
+
+            let mut input = String::new();
+            io::stdin().read_line(&mut input)?;
+            let input = input.trim();
+
+            if input == "q" {
+                break;
+            } else if let Some(query) = input.strip_prefix('/') {
+                filter = query.trim().to_string();
+            } else if input == "sort" {
+                sort = sort.next();
+            } else if let Some(folder) = input.strip_prefix("f ") {
+                let folder = folder.trim().to_string();
+                if !collapsed.remove(&folder) {
+                    collapsed.insert(folder);
+                }
+            } else if let Some(num) = input.strip_prefix("e ") {
+                if let Ok(index) = num.trim().parse::<usize>() {
+                    if index > 0 && index <= visible.len() {
+                        self.edit_bookmark(visible[index - 1])?;
+                    }
+                }
+            } else if let Some(args) = input.strip_prefix("k ") {
+                let mut args = args.split_whitespace();
+                let index = args.next().and_then(|num| num.parse::<usize>().ok());
+                let key = args.next().and_then(quickmark_key);
+                if let Some(index) = index.filter(|&i| i > 0 && i <= visible.len()) {
+                    self.set_quickmark(visible[index - 1], key)?;
+                }
+            } else if input.starts_with('d') {
+                if let Some(num) = input.split_whitespace().nth(1) {
+                    if let Ok(index) = num.parse::<usize>() {
+                        if index > 0 && index <= visible.len() {
+                            self.bookmarks.remove(visible[index - 1]);
+                            self.save_bookmarks()?;
+                            toast::show("Bookmark deleted!", Color::DarkGreen)?;
+                        }
+                    }
+                }
+            } else if let Ok(index) = input.parse::<usize>() {
+                if index > 0 && index <= visible.len() {
+                    let url = self.bookmarks[visible[index - 1]].url.clone();
+                    self.navigate(&url)?;
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn show_history(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        loop {
+            execute!(io::stdout(), Clear(ClearType::All))?;
+
+            execute!(
+                io::stdout(),
+                SetBackgroundColor(Color::DarkBlue),
+                SetForegroundColor(Color::White)
+            )?;
+            println!(" Browsing History ");
+            execute!(io::stdout(), ResetColor)?;
+            println!();
+
+            for (i, entry) in self.history.iter().enumerate() {
+                execute!(io::stdout(), SetForegroundColor(Color::DarkGrey))?;
+                print!(" {}. ", i + 1);
+
+                execute!(io::stdout(), SetForegroundColor(Color::Blue))?;
+                print!("{} ", entry.url);
+
+                let visited: DateTime<Local> = entry.visited.into();
+                execute!(io::stdout(), SetForegroundColor(Color::DarkGrey))?;
+                println!("({})", visited.format("%Y-%m-%d %H:%M"));
+            }
+
+            execute!(io::stdout(), ResetColor)?;
+            println!("\nCommands:");
+            println!("number - Go to URL from history");
+            println!("q - Return to browser");
+
+            print!("\nEnter command: ");
+            io::stdout().flush()?;
+
+            let mut input = String::new();
+            io::stdin().read_line(&mut input)?;
+            let input = input.trim();
+
+            if input == "q" {
+                break;
+            } else if let Ok(index) = input.parse::<usize>() {
+                if index > 0 && index <= self.history.len() {
+                    let url = self.history[index - 1].url.clone();
+                    self.navigate(&url)?;
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn read_later_add(&mut self) -> io::Result<()> {
+        let url = match &self.tab().current_url {
+            Some(url) => url.clone(),
+            None => {
+                println!("No page loaded.");
+                return Ok(());
+            }
+        };
+        if self.read_later.iter().any(|entry| entry.url == url) {
+            println!("Already in the read-later queue.");
+            return Ok(());
+        }
+        self.read_later.push(QueueEntry {
+            title: self.tab().title.clone().unwrap_or_else(|| url.clone()),
+            url,
+            added: Utc::now(),
+        });
+        self.save_read_later()?;
+        println!(
+            "Added to the read-later queue ({} queued).",
+            self.read_later.len()
+        );
+        Ok(())
+    }
+
+    fn open_queued(&mut self, index: usize) -> Result<(), Box<dyn std::error::Error>> {
+        let url = self.read_later[index].url.clone();
+        self.navigate(&url)?;
+        if self.config.queue_auto_remove {
+            self.read_later.remove(index);
+            self.save_read_later()?;
+        }
+        Ok(())
+    }
+
+    fn show_queue(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        loop {
+            execute!(io::stdout(), Clear(ClearType::All))?;
+
+            execute!(
+                io::stdout(),
+                SetBackgroundColor(Color::DarkBlue),
+                SetForegroundColor(Color::White)
+            )?;
+            println!(" Read Later ");
+            execute!(io::stdout(), ResetColor)?;
+            println!();
+
+            if self.read_later.is_empty() {
+                execute!(io::stdout(), SetForegroundColor(Color::Red))?;
+                println!("The queue is empty. Use ql to add the current page.");
+            }
+
+            for (i, entry) in self.read_later.iter().enumerate() {
+                execute!(io::stdout(), SetForegroundColor(Color::DarkGrey))?;
+                print!(" {}. ", i + 1);
+
+                execute!(io::stdout(), SetForegroundColor(Color::White))?;
+                print!("{} ", entry.title);
+
+                execute!(io::stdout(), SetForegroundColor(Color::Blue))?;
+                print!("({}) ", entry.url);
+
+                let added: DateTime<Local> = entry.added.into();
+                execute!(io::stdout(), SetForegroundColor(Color::DarkGrey))?;
+                println!("{}", added.format("%Y-%m-%d"));
+            }
+
+            execute!(io::stdout(), ResetColor)?;
+            println!("\nCommands:");
+            if self.config.queue_auto_remove {
+                println!("number - Open and remove from the queue");
+            } else {
+                println!("number - Open");
+            }
+            println!("p - Pop: open the oldest entry and remove it");
+            println!("d number - Remove entry");
+            println!("q - Return to browser");
+
+            print!("\nEnter command: ");
+            io::stdout().flush()?;
+
+            let mut input = String::new();
+            io::stdin().read_line(&mut input)?;
+            let input = input.trim();
+
+            if input == "q" {
+                break;
+            } else if input == "p" {
+                if let Some(entry) = self.read_later.first() {
+                    let url = entry.url.clone();
+                    match self.navigate(&url) {
+                        Ok(()) => {
+                            self.read_later.remove(0);
+                            self.save_read_later()?;
+                        }
+                        Err(e) => self.report_error(&e)?,
+                    }
+                    break;
+                }
+            } else if let Some(num) = input.strip_prefix("d ") {
+                if let Ok(index) = num.trim().parse::<usize>() {
+                    if index > 0 && index <= self.read_later.len() {
+                        self.read_later.remove(index - 1);
+                        self.save_read_later()?;
+                    }
+                }
+            } else if let Ok(index) = input.parse::<usize>() {
+                if index > 0 && index <= self.read_later.len() {
+                    if let Err(e) = self.open_queued(index - 1) {
+                        self.report_error(&e)?;
+                    }
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn show_top(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        loop {
+            execute!(io::stdout(), Clear(ClearType::All))?;
+
+            execute!(
+                io::stdout(),
+                SetBackgroundColor(Color::DarkBlue),
+                SetForegroundColor(Color::White)
+            )?;
+            println!(" Most Visited ");
+            execute!(io::stdout(), ResetColor)?;
+            println!();
+
+            let ranked = self.most_visited();
+            if ranked.is_empty() {
+                execute!(io::stdout(), SetForegroundColor(Color::Red))?;
+                println!("No visits recorded yet.");
+            }
+
+            for (i, (url, count)) in ranked.iter().enumerate() {
+                execute!(io::stdout(), SetForegroundColor(Color::DarkGrey))?;
+                print!(" {}. ", i + 1);
+
+                execute!(io::stdout(), SetForegroundColor(Color::Blue))?;
+                print!("{} ", url);
+
+                execute!(io::stdout(), SetForegroundColor(Color::DarkGrey))?;
+                println!("({} visits)", count);
+            }
+
+            execute!(io::stdout(), ResetColor)?;
+            println!("\nCommands:");
+            println!("number - Go to URL");
+            println!("q - Return to browser");
+
+            print!("\nEnter command: ");
+            io::stdout().flush()?;
+
+            let mut input = String::new();
+            io::stdin().read_line(&mut input)?;
+            let input = input.trim();
+
+            if input == "q" {
+                break;
+            } else if let Ok(index) = input.parse::<usize>() {
+                if index > 0 && index <= ranked.len() {
+                    let url = ranked[index - 1].0.to_string();
+                    self.navigate(&url)?;
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn link_texts(&self) -> Vec<String> {
+        let tab = self.tab();
+        let html = match (&tab.article_html, &tab.raw_html) {
+            (Some(article), _) if tab.reader_mode => article,
+            (_, Some(html)) => html,
+            _ => return vec![String::new(); tab.links.len()],
+        };
+        let document = dom::parse(html).document;
+        let anchors: Vec<(String, String)> = dom::find_all(&document, "a")
+            .iter()
+            .filter_map(|anchor| {
+                let href = dom::attr(anchor, "href")?;
+                let text = dom::text_of(anchor)
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                Some((href, text))
+            })
+            .collect();
+
+        let mut next = 0;
+        tab.links
+            .iter()
+            .map(
+                |link| match anchors[next..].iter().position(|(href, _)| href == link) {
+                    Some(offset) => {
+                        next += offset + 1;
+                        anchors[next - 1].1.clone()
+                    }
+                    None => String::new(),
+                },
+            )
+            .collect()
+    }
+
+    fn show_links(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let texts = self.link_texts();
+        let links: Vec<String> = self
+            .tab()
+            .links
+            .iter()
+            .map(|href| self.resolve_link(href).unwrap_or_else(|| href.clone()))
+            .collect();
+
+        loop {
+            execute!(io::stdout(), Clear(ClearType::All))?;
+
+            execute!(
+                io::stdout(),
+                SetBackgroundColor(Color::DarkBlue),
+                SetForegroundColor(Color::White)
+            )?;
+            println!(" Links ");
+            execute!(io::stdout(), ResetColor)?;
+            println!();
+
+            if links.is_empty() {
+                execute!(io::stdout(), SetForegroundColor(Color::Red))?;
+                println!("No links found on this page.");
+            }
+
+            for (i, (url, text)) in links.iter().zip(&texts).enumerate() {
+                execute!(io::stdout(), SetForegroundColor(Color::DarkGrey))?;
+                print!(" {}. ", i + 1);
+
+                if !text.is_empty() {
+                    execute!(io::stdout(), SetForegroundColor(Color::White))?;
+                    print!("{} ", text);
+                }
+
+                execute!(io::stdout(), SetForegroundColor(Color::Blue))?;
+                println!("({})", url);
+            }
+
+            execute!(io::stdout(), ResetColor)?;
+            println!("\nCommands:");
+            println!("number - Follow link");
+            println!("q - Return to browser");
+
+            print!("\nEnter command: ");
+            io::stdout().flush()?;
+
+            let mut input = String::new();
+            io::stdin().read_line(&mut input)?;
+            let input = input.trim();
+
+            if input == "q" {
+                break;
+            } else if let Ok(index) = input.parse::<usize>() {
+                if index > 0 && index <= links.len() {
+                    self.navigate(&links[index - 1])?;
+                    return Ok(());
+                }
+            }
+        }
+        Ok(self.display_page()?)
+    }
+
+    fn show_forms(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let forms = match &self.tab().raw_html {
+            Some(html) => forms::parse(html),
+            None => Vec::new(),
+        };
+        if forms.is_empty() {
+            println!("No forms found on this page.");
+            return Ok(());
+        }
+
+        loop {
+            execute!(io::stdout(), Clear(ClearType::All))?;
+
+            execute!(
+                io::stdout(),
+                SetBackgroundColor(Color::DarkBlue),
+                SetForegroundColor(Color::White)
+            )?;
+            println!(" Forms ");
+            execute!(io::stdout(), ResetColor)?;
+            println!();
+
+            for (i, form) in forms.iter().enumerate() {
+                execute!(io::stdout(), SetForegroundColor(Color::DarkGrey))?;
+                print!(" {}. ", i + 1);
+                execute!(io::stdout(), SetForegroundColor(Color::Yellow))?;
+                print!("{} ", form.method);
+                execute!(io::stdout(), SetForegroundColor(Color::Blue))?;
+                println!("{}", self.resolve_link(&form.action).unwrap_or_default());
+
+                execute!(io::stdout(), SetForegroundColor(Color::White))?;
+                for field in form.fields.iter().filter(|field| field.is_editable()) {
+                    println!("      {} ({}) = {:?}", field.name, field.kind, field.value);
+                }
+            }
+
+            execute!(io::stdout(), ResetColor)?;
+            println!("\nCommands:");
+            println!("number - Fill in and submit a form");
+            println!("q - Return to browser");
+
+            print!("\nEnter command: ");
+            io::stdout().flush()?;
+
+            let mut input = String::new();
+            io::stdin().read_line(&mut input)?;
+            let input = input.trim();
+
+            if input == "q" {
+                break;
+            } else if let Ok(index) = input.parse::<usize>() {
+                if index > 0 && index <= forms.len() {
+                    let form = forms.into_iter().nth(index - 1).unwrap();
+                    self.submit_form(form)?;
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn submit_form(&mut self, mut form: Form) -> Result<(), Box<dyn std::error::Error>> {
+        if form.method != "GET" {
+            println!("Only GET forms can be submitted for now.");
+            return Ok(());
+        }
+
+        for field in form.fields.iter_mut().filter(|field| field.is_editable()) {
+            print!("{} [{}]: ", field.name, field.value);
+            io::stdout().flush()?;
+            let mut value = String::new();
+            io::stdin().read_line(&mut value)?;
+            let value = value.trim_end_matches(['\r', '\n']);
+            if !value.is_empty() {
+                field.value = value.to_string();
+            }
+        }
+
+        let action = self
+            .resolve_link(&form.action)
+            .ok_or("Cannot resolve the form action")?;
+        let mut url = Url::parse(&action)?;
+        url.set_fragment(None);
+        url.query_pairs_mut()
+            .clear()
+            .extend_pairs(form.fields.iter().map(|field| (&field.name, &field.value)));
+        self.navigate(url.as_str())
+    }
+
+    fn headings(&self) -> Vec<(usize, usize, String)> {
+        self.tab()
+            .page_content
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let line = line.trim();
+                let level = line.chars().take_while(|&c| c == '#').count();
+                let title = line[level..].strip_prefix(' ')?;
+                if level == 0 || title.trim().is_empty() {
+                    return None;
+                }
+                Some((i, level, title.trim().to_string()))
+            })
+            .collect()
+    }
+
+    fn completions(&self, line: &str) -> Vec<String> {
+        if let Some(prefix) = line.strip_prefix("g ") {
+            let mut urls: Vec<&str> = self
+                .history
+                .iter()
+                .map(|entry| entry.url.as_str())
+                .chain(self.bookmarks.iter().map(|bookmark| bookmark.url.as_str()))
+                .filter(|url| {
+                    url.starts_with(prefix)
+                        || url
+                            .split_once("://")
+                            .is_some_and(|(_, rest)| rest.starts_with(prefix))
+                })
+                .collect();
+            urls.sort();
+            urls.dedup();
+            return urls.into_iter().map(|url| format!("g {}", url)).collect();
+        }
+
+        if line.contains(' ') {
+            return Vec::new();
+        }
+        commands::COMMANDS
+            .iter()
+            .filter(|command| command.name.len() > 1 && command.name.starts_with(line))
+            .map(|command| command.name.to_string())
+            .collect()
+    }
+
+    fn show_toc(&mut self) -> io::Result<()> {
+        let headings = self.headings();
+
+        loop {
+            execute!(io::stdout(), Clear(ClearType::All))?;
+
+            execute!(
+                io::stdout(),
+                SetBackgroundColor(Color::DarkCyan),
+                SetForegroundColor(Color::White)
+            )?;
+            println!(" Table of Contents ");
+            execute!(io::stdout(), ResetColor)?;
+            println!();
+
+            if headings.is_empty() {
+                execute!(io::stdout(), SetForegroundColor(Color::Red))?;
+                println!("No headings found.");
+            }
+
+            for (i, (line, level, title)) in headings.iter().enumerate() {
+                execute!(io::stdout(), SetForegroundColor(Color::DarkGrey))?;
+                print!(" {}. ", i + 1);
+
+                execute!(io::stdout(), SetForegroundColor(Color::Cyan))?;
+                print!("{}{} ", "  ".repeat(level - 1), title);
+
+                execute!(io::stdout(), SetForegroundColor(Color::DarkGrey))?;
+                println!("(line {})", line + 1);
+            }
+
+            execute!(io::stdout(), ResetColor)?;
+            println!("\nCommands:");
+            println!("number - Jump to heading");
+            println!("q - Return to browser");
+
+            print!("\nEnter command: ");
+            io::stdout().flush()?;
+
+            let mut input = String::new();
+            io::stdin().read_line(&mut input)?;
+            let input = input.trim();
+
+            if input == "q" {
+                break;
+            } else if let Ok(index) = input.parse::<usize>() {
+                if index > 0 && index <= headings.len() {
+                    self.tab_mut().scroll_position = headings[index - 1].0;
+                    break;
+                }
+            }
+        }
+        self.display_page()
+    }
+
+    fn show_headers(&self) -> io::Result<()> {
+        let status = match self.tab().status {
+            Some(status) => status,
+            None => {
+                println!("No page loaded.");
+                return Ok(());
+            }
+        };
+
+        execute!(io::stdout(), Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+        execute!(
+            io::stdout(),
+            SetBackgroundColor(Color::DarkGreen),
+            SetForegroundColor(Color::White)
+        )?;
+        println!(" Response Headers ");
+        execute!(io::stdout(), ResetColor)?;
+        println!();
+
+        let color = if status.is_success() {
+            Color::Green
+        } else {
+            Color::Red
+        };
+        execute!(io::stdout(), SetForegroundColor(color))?;
+        println!(" {:?} {}", self.tab().version, status);
+        println!();
+
+        for (name, value) in &self.tab().response_headers {
+            execute!(io::stdout(), SetForegroundColor(Color::Yellow))?;
+            print!(" {}: ", name);
+
+            execute!(io::stdout(), SetForegroundColor(Color::White))?;
+            println!("{}", value.to_str().unwrap_or("<binary>"));
+        }
+
+        execute!(io::stdout(), ResetColor)?;
+        Ok(())
+    }
+
+    fn show_tls(&self) -> io::Result<()> {
+        let der = match &self.tab().peer_certificate {
+            Some(der) => der,
+            None => {
+                println!("The current page was not loaded over TLS.");
+                return Ok(());
+            }
+        };
+        let certificate = match x509_parser::parse_x509_certificate(der) {
+            Ok((_, certificate)) => certificate,
+            Err(e) => {
+                println!("Could not parse the server certificate: {}", e);
+                return Ok(());
+            }
+        };
+
+        execute!(io::stdout(), Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+        execute!(
+            io::stdout(),
+            SetBackgroundColor(Color::DarkGreen),
+            SetForegroundColor(Color::White)
+        )?;
+        println!(" TLS Certificate ");
+        execute!(io::stdout(), ResetColor)?;
+        println!();
+
+        let validity = certificate.validity();
+        let date = |timestamp: i64| {
+            DateTime::from_timestamp(timestamp, 0)
+                .map(|date| date.format("%Y-%m-%d %H:%M UTC").to_string())
+                .unwrap_or_default()
+        };
+        let not_after = date(validity.not_after.timestamp());
+        let expiry = match validity.time_to_expiration() {
+            Some(remaining) => format!("{} ({} days left)", not_after, remaining.whole_days()),
+            None => format!("{} (expired)", not_after),
+        };
+        let fields = [
+            ("Subject", certificate.subject().to_string()),
+            ("Issuer", certificate.issuer().to_string()),
+            ("Valid from", date(validity.not_before.timestamp())),
+            ("Valid until", expiry),
+            ("HTTP version", format!("{:?}", self.tab().version)),
+        ];
+        for (name, value) in fields {
+            execute!(io::stdout(), SetForegroundColor(Color::Yellow))?;
+            print!(" {}: ", name);
+
+            execute!(io::stdout(), SetForegroundColor(Color::White))?;
+            println!("{}", value);
+        }
+
+        if self.options.accept_invalid_certs {
+            execute!(io::stdout(), SetForegroundColor(Color::Red))?;
+            println!(
+                "\nCertificate validation is disabled ({}). Connections are not secure.",
+                INSECURE_FLAG
+            );
+        }
+
+        execute!(io::stdout(), ResetColor)?;
+        Ok(())
+    }
+
+    fn show_cookies(&self) -> io::Result<()> {
+        let url = match self
+            .tab()
+            .current_url
+            .as_deref()
+            .and_then(|u| Url::parse(u).ok())
+        {
+            Some(url) => url,
+            None => {
+                println!("No page loaded.");
+                return Ok(());
+            }
+        };
+
+        execute!(io::stdout(), Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+        execute!(
+            io::stdout(),
+            SetBackgroundColor(Color::DarkYellow),
+            SetForegroundColor(Color::White)
+        )?;
+        println!(" Cookies for {} ", url.host_str().unwrap_or(""));
+        execute!(io::stdout(), ResetColor)?;
+        println!();
+
+        let store = self
+            .options
+            .cookies
+            .lock()
+            .map_err(|_| io::Error::other("cookie store lock poisoned"))?;
+        let cookies = store.matches(&url);
+
+        if cookies.is_empty() {
+            execute!(io::stdout(), SetForegroundColor(Color::Red))?;
+            println!("No cookies stored for this site.");
+        }
+
+        for cookie in cookies {
+            execute!(io::stdout(), SetForegroundColor(Color::Yellow))?;
+            print!(" {}", cookie.name());
+
+            execute!(io::stdout(), SetForegroundColor(Color::White))?;
+            print!(" = {} ", cookie.value());
+
+            execute!(io::stdout(), SetForegroundColor(Color::DarkGrey))?;
+            println!(
+                "({}{})",
+                cookie.domain().unwrap_or(url.host_str().unwrap_or("")),
+                cookie.path().unwrap_or("/")
+            );
+        }
+
+        execute!(io::stdout(), ResetColor)?;
+        Ok(())
+    }
+
+    fn view_page_source(&mut self) -> io::Result<()> {
+        if self.tab().current_url.is_none() {
+            println!("Unable to fetch page source");
+            return Ok(());
+        }
+
+        let content_type = self
+            .tab()
+            .response_headers
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("")
+            .to_string();
+        let source = Self::decode_body(&self.tab().body, &content_type);
+        let mut highlighter = Highlighter::new(Language::from_content_type(&content_type));
+        let lines: Vec<Vec<(Color, String)>> = source
+            .lines()
+            .map(|line| highlighter.highlight(line))
+            .collect();
+
+        let mut position = 0;
         loop {
-            execute!(io::stdout(), Clear(ClearType::All))?;
+            let height = Self::viewport_height()?;
+            let max_scroll = lines.len().saturating_sub(height);
+            position = position.min(max_scroll);
 
+            execute!(io::stdout(), Clear(ClearType::All), cursor::MoveTo(0, 0))?;
             execute!(
                 io::stdout(),
-                SetBackgroundColor(Color::Magenta),
-                SetForegroundColor(Color::White)
+                SetBackgroundColor(self.theme.header_bg),
+                SetForegroundColor(self.theme.header_fg)
+            )?;
+            println!(" Page Source ");
+            execute!(io::stdout(), ResetColor)?;
+            println!();
+
+            for (i, spans) in lines.iter().enumerate().skip(position).take(height) {
+                execute!(io::stdout(), SetForegroundColor(Color::DarkGrey))?;
+                print!("{:4} │ ", i + 1);
+                for (color, text) in spans {
+                    execute!(io::stdout(), SetForegroundColor(*color))?;
+                    print!("{}", text);
+                }
+                println!();
+            }
+
+            execute!(
+                io::stdout(),
+                cursor::MoveTo(0, (height + 3) as u16),
+                ResetColor,
+                SetForegroundColor(Color::DarkGrey)
+            )?;
+            println!(
+                " Lines: {} | Position: {} | [w/s scroll] [g/G top/bottom] [q back]",
+                lines.len(),
+                position + 1
+            );
+            io::stdout().flush()?;
+
+            enable_raw_mode()?;
+            let event = read();
+            disable_raw_mode()?;
+
+            if let Event::Key(key) = event? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => break,
+                    KeyCode::Char('w') | KeyCode::Up => position = position.saturating_sub(1),
+                    KeyCode::Char('s') | KeyCode::Down => position += 1,
+                    KeyCode::PageUp => position = position.saturating_sub(height),
+                    KeyCode::PageDown | KeyCode::Char(' ') => position += height,
+                    KeyCode::Char('g') | KeyCode::Home => position = 0,
+                    KeyCode::Char('G') | KeyCode::End => position = max_scroll,
+                    _ => {}
+                }
+            }
+        }
+
+        self.display_page()
+    }
+
+    fn toggle_json_pretty(&mut self) -> io::Result<()> {
+        let tab = self.tab_mut();
+        let tree = match &tab.json_tree {
+            Some(tree) => tree,
+            None => {
+                println!("The current page is not JSON.");
+                return Ok(());
+            }
+        };
+        tab.json_pretty = !tab.json_pretty;
+        let text = if tab.json_pretty {
+            tree.pretty()
+        } else {
+            tree.render()
+        };
+        tab.page_content = entities::decode(&text).into_owned();
+        tab.scroll_position = 0;
+        tab.search_matches.clear();
+        self.finish_page_content();
+        self.display_page()
+    }
+
+    fn query_json(&mut self, query: &str) -> io::Result<()> {
+        let tab = self.tab_mut();
+        let tree = match &tab.json_tree {
+            Some(tree) => tree,
+            None => {
+                println!("The current page is not JSON.");
+                return Ok(());
+            }
+        };
+        let selected = match tree.select(query) {
+            Ok(value) => serde_json::to_string_pretty(value).unwrap_or_default(),
+            Err(e) => return self.report_error(&e),
+        };
+        tab.page_content = format!("# {}\n{}", query, selected);
+        tab.json_pretty = true;
+        tab.scroll_position = 0;
+        tab.search_matches.clear();
+        self.finish_page_content();
+        self.display_page()
+    }
+
+    fn explore_json(&mut self) -> io::Result<()> {
+        let mut tree = match self.tab_mut().json_tree.take() {
+            Some(tree) => tree,
+            None => {
+                println!("The current page is not JSON.");
+                return Ok(());
+            }
+        };
+
+        let mut selected = 0;
+        let mut position = 0;
+        loop {
+            let lines = tree.lines();
+            let height = Self::viewport_height()?;
+            selected = selected.min(lines.len().saturating_sub(1));
+            if selected < position {
+                position = selected;
+            } else if selected >= position + height {
+                position = selected + 1 - height;
+            }
+
+            execute!(io::stdout(), Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+            execute!(
+                io::stdout(),
+                SetBackgroundColor(self.theme.header_bg),
+                SetForegroundColor(self.theme.header_fg)
+            )?;
+            println!(" JSON Tree ");
+            execute!(io::stdout(), ResetColor)?;
+            println!();
+
+            for (i, line) in lines.iter().enumerate().skip(position).take(height) {
+                if i == selected {
+                    execute!(
+                        io::stdout(),
+                        SetBackgroundColor(Color::DarkBlue),
+                        SetForegroundColor(Color::White)
+                    )?;
+                } else if line.is_container {
+                    execute!(io::stdout(), SetForegroundColor(Color::Yellow))?;
+                } else {
+                    execute!(io::stdout(), SetForegroundColor(Color::White))?;
+                }
+                print!("{}", line.text);
+                execute!(io::stdout(), ResetColor)?;
+                println!();
+            }
+
+            execute!(
+                io::stdout(),
+                cursor::MoveTo(0, (height + 3) as u16),
+                ResetColor,
+                SetForegroundColor(Color::DarkGrey)
+            )?;
+            println!(
+                " Node {} of {} | [w/s move] [enter toggle] [a/d collapse/expand] [q back]",
+                selected + 1,
+                lines.len()
+            );
+            io::stdout().flush()?;
+
+            enable_raw_mode()?;
+            let event = read();
+            disable_raw_mode()?;
+
+            if let Event::Key(key) = event? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                let path = &lines[selected].path;
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => break,
+                    KeyCode::Char('w') | KeyCode::Up => selected = selected.saturating_sub(1),
+                    KeyCode::Char('s') | KeyCode::Down => selected += 1,
+                    KeyCode::PageUp => selected = selected.saturating_sub(height),
+                    KeyCode::PageDown => selected += height,
+                    KeyCode::Char('g') | KeyCode::Home => selected = 0,
+                    KeyCode::Char('G') | KeyCode::End => selected = lines.len(),
+                    KeyCode::Enter | KeyCode::Char(' ') => tree.toggle(path),
+                    KeyCode::Char('a') | KeyCode::Left => tree.collapse(path),
+                    KeyCode::Char('d') | KeyCode::Right => tree.expand(path),
+                    _ => {}
+                }
+            }
+        }
+
+        let tab = self.tab_mut();
+        tab.page_content = entities::decode(&tree.render()).into_owned();
+        tab.json_tree = Some(tree);
+        tab.json_pretty = false;
+        tab.search_matches.clear();
+        self.finish_page_content();
+        self.display_page()
+    }
+
+    fn archive_page(&self, filename: &str) -> io::Result<()> {
+        let tab = self.tab();
+        let url = match &tab.current_url {
+            Some(url) => url.clone(),
+            None => {
+                println!("No page loaded.");
+                return Ok(());
+            }
+        };
+        let headers = tab
+            .response_headers
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        archive::write(
+            filename,
+            &archive::Record {
+                url,
+                archived: Utc::now(),
+                status: tab.status.unwrap_or(StatusCode::OK).as_u16(),
+                headers,
+                body: tab.body.clone(),
+            },
+        )?;
+        println!("Page archived to: {}", filename);
+        Ok(())
+    }
+
+    fn view_archive(&mut self, filename: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let record = archive::read(filename)?;
+        let mut headers = HeaderMap::new();
+        for (name, value) in &record.headers {
+            headers.append(
+                HeaderName::from_bytes(name.as_bytes())?,
+                HeaderValue::from_str(value)?,
+            );
+        }
+
+        self.begin_page(&record.url);
+        let tab = self.tab_mut();
+        tab.status = Some(StatusCode::from_u16(record.status)?);
+        tab.version = Version::default();
+        tab.response_headers = headers;
+        tab.body = record.body;
+        tab.redirects = 0;
+        tab.elapsed = None;
+        tab.peer_certificate = None;
+        self.render_body()?;
+
+        let archived: DateTime<Local> = record.archived.into();
+        println!("Archived copy from {}", archived.format("%Y-%m-%d %H:%M"));
+        Ok(())
+    }
+
+    fn save_markdown(&self, filename: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let tab = self.tab();
+        if tab.current_url.is_none() {
+            println!("No page loaded.");
+            return Ok(());
+        }
+
+        let html = match (&tab.article_html, &tab.raw_html) {
+            (Some(article), _) if tab.reader_mode => Some(article),
+            (_, html) => html.as_ref(),
+        };
+        let markdown = match html {
+            Some(html) => {
+                let base = tab
+                    .current_url
+                    .as_deref()
+                    .and_then(|url| Url::parse(url).ok());
+                let decorator = MarkdownDecorator::new(base);
+                html2text::from_read_with_decorator(
+                    html.as_bytes(),
+                    DEFAULT_RENDER_WIDTH,
+                    decorator,
+                )
+            }
+            None => tab.page_content.clone(),
+        };
+
+        let mut file = File::create(filename)?;
+        file.write_all(markdown.as_bytes())?;
+        println!("Page saved as Markdown to: {}", filename);
+        Ok(())
+    }
+
+    fn save_snapshot(&mut self, filename: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let url = match &self.tab().current_url {
+            Some(url) => url.clone(),
+            None => {
+                println!("No page loaded.");
+                return Ok(());
+            }
+        };
+        let html = match &self.tab().raw_html {
+            Some(html) => html,
+            None => {
+                println!("Snapshots are only available for HTML pages.");
+                return Ok(());
+            }
+        };
+        if self.tab().article_html.is_none() {
+            self.tab_mut().article_html = reader::extract(html);
+        }
+        let article = match &self.tab().article_html {
+            Some(article) => article.clone(),
+            None => {
+                println!("Could not find an article on this page.");
+                return Ok(());
+            }
+        };
+
+        let title = self.tab().title.clone().unwrap_or_else(|| url.clone());
+        let saved = Local::now().format("%Y-%m-%d %H:%M").to_string();
+        let mut inlined = 0;
+        let mut failure = None;
+        let document = snapshot::build(&article, &title, &url, &saved, |src| {
+            if inlined >= MAX_SNAPSHOT_IMAGES || failure.is_some() {
+                return None;
+            }
+            match self.image_data_uri(src) {
+                Ok(image) => {
+                    inlined += image.is_some() as usize;
+                    image
+                }
+                Err(e) => {
+                    failure = Some(e);
+                    None
+                }
+            }
+        });
+        if let Some(e) = failure {
+            return Err(e);
+        }
+        std::fs::write(filename, document)?;
+        println!(
+            "Snapshot saved to {} ({} images inlined)",
+            filename, inlined
+        );
+        Ok(())
+    }
+
+    fn image_data_uri(&mut self, url: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Ok(None);
+        }
+        if self.check_blocklist(url).is_err() || self.resolve_via_doh(url).is_err() {
+            return Ok(None);
+        }
+        let request = self.authorize(self.client.get(url), url);
+        self.start_spinner(url);
+        let image = self.interruptible(move || fetch_image(request));
+        self.loading = None;
+        match image? {
+            Some(image) => Ok(image),
+            None => Err("Snapshot cancelled".into()),
+        }
+    }
+
+    fn download_page(&mut self, filename: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let url = match &self.tab().current_url {
+            Some(url) => url.clone(),
+            None => {
+                println!("No page loaded.");
+                return Ok(());
+            }
+        };
+        let max = self.config.max_downloads;
+        let active = self
+            .downloads
+            .iter()
+            .filter(|download| matches!(download.state(), State::Active))
+            .count();
+        if max > 0 && active >= max {
+            println!(
+                "{} downloads are already running (max_downloads = {}).",
+                active, max
+            );
+            return Ok(());
+        }
+        self.check_blocklist(&url)?;
+        self.throttle(&url);
+        self.resolve_via_doh(&url)?;
+        let request = self.authorize(self.client.get(&url), &url);
+        if let Ok(metadata) = std::fs::metadata(filename) {
+            if downloads::can_resume(filename) {
+                println!(
+                    "{} is partly downloaded ({}); resuming if the file has not changed.",
+                    filename,
+                    format_size(metadata.len())
+                );
+            } else {
+                println!("{} already exists and will be overwritten.", filename);
+            }
+        }
+        self.downloads
+            .push(Download::start(request, &url, filename));
+        println!(
+            "Downloading to {} in the background. Type 'downloads' to check progress.",
+            filename
+        );
+        Ok(())
+    }
+
+    fn show_downloads(&self) -> io::Result<()> {
+        if self.downloads.is_empty() {
+            println!("No downloads yet.");
+            return Ok(());
+        }
+
+        for (i, download) in self.downloads.iter().enumerate() {
+            execute!(io::stdout(), SetForegroundColor(Color::DarkGrey))?;
+            print!(" {}. ", i + 1);
+
+            execute!(io::stdout(), SetForegroundColor(Color::White))?;
+            print!("{} ", download.filename);
+
+            let downloaded = download.downloaded();
+            let progress = match download.total() {
+                Some(total) => format!(
+                    "{} / {} ({}%)",
+                    format_size(downloaded),
+                    format_size(total),
+                    downloaded * 100 / total
+                ),
+                None => format_size(downloaded),
+            };
+            match download.state() {
+                State::Active => {
+                    execute!(io::stdout(), SetForegroundColor(Color::Yellow))?;
+                    print!("{}", progress);
+                }
+                State::Finished => {
+                    execute!(io::stdout(), SetForegroundColor(Color::Green))?;
+                    print!("done, {}", format_size(downloaded));
+                }
+                State::Failed(e) => {
+                    execute!(io::stdout(), SetForegroundColor(Color::Red))?;
+                    print!("failed: {}", e);
+                }
+            }
+
+            execute!(io::stdout(), SetForegroundColor(Color::Blue))?;
+            println!(" ({})", download.url);
+        }
+
+        execute!(io::stdout(), ResetColor)?;
+        Ok(())
+    }
+
+    fn print_highlighted(
+        line: &str,
+        pattern: &Pattern,
+        color: Color,
+        background: Color,
+    ) -> io::Result<()> {
+        let mut last_pos = 0;
+
+        for (start, end) in pattern.ranges(line) {
+            print!("{}", &line[last_pos..start]);
+
+            execute!(
+                io::stdout(),
+                SetBackgroundColor(background),
+                SetForegroundColor(Color::Black)
             )?;
-            println!(" Bookmarks ");
+            print!("{}", &line[start..end]);
+            execute!(io::stdout(), ResetColor, SetForegroundColor(color))?;
+
+            last_pos = end;
+        }
+
+        print!("{}", &line[last_pos..]);
+        Ok(())
+    }
+
+    fn search_in_page(&mut self, query: &str) -> io::Result<()> {
+        let pattern = match Pattern::parse(query, self.case_sensitive) {
+            Ok(pattern) => pattern,
+            Err(e) => {
+                println!("Invalid pattern: {}", e);
+                return Ok(());
+            }
+        };
+
+        execute!(io::stdout(), Clear(ClearType::All))?;
+
+        execute!(
+            io::stdout(),
+            SetBackgroundColor(Color::Yellow),
+            SetForegroundColor(Color::Black)
+        )?;
+        println!(" Search Results: \"{}\" ", query);
+        execute!(io::stdout(), ResetColor)?;
+        println!();
+
+        self.tab_mut().search_matches = matching_lines(&self.tab().page_content, &pattern);
+        self.tab_mut().search_index = 0;
+
+        let lines: Vec<&str> = self.tab().page_content.lines().collect();
+        for &i in &self.tab().search_matches {
+            execute!(io::stdout(), SetForegroundColor(Color::DarkGrey))?;
+            print!("{:4} │ ", i + 1);
             execute!(io::stdout(), ResetColor)?;
+            Self::print_highlighted(lines[i], &pattern, Color::Reset, Color::Yellow)?;
             println!();
+        }
 
-            for (i, bookmark) in self.bookmarks.iter().enumerate() {
-                execute!(io::stdout(), SetForegroundColor(Color::Yellow))?;
-                print!(" {}. ", i + 1);
+        if self.tab().search_matches.is_empty() {
+            execute!(io::stdout(), SetForegroundColor(Color::Red))?;
+            println!("No matches found.");
+            self.tab_mut().search_pattern = None;
+        } else {
+            self.tab_mut().search_pattern = Some(pattern);
+        }
 
-                execute!(io::stdout(), SetForegroundColor(Color::White))?;
-                print!("{} ", bookmark.title);
+        execute!(io::stdout(), ResetColor)?;
+        if self.tab().search_matches.is_empty() {
+            println!("\nPress any key to return...");
+        } else {
+            println!("\nPress Enter to step through the matches, any other key to return...");
+        }
+        io::stdout().flush()?;
 
-                execute!(io::stdout(), SetForegroundColor(Color::Blue))?;
-                println!("({})", bookmark.url);
-            }
+        enable_raw_mode()?;
+        let key = read()?;
+        disable_raw_mode()?;
 
-            execute!(io::stdout(), ResetColor)?;
-            println!("\nCommands:");
-            println!("number - Go to bookmark");
-            println!("d number - Delete bookmark");
-            println!("q - Return to browser");
+        if self.tab().search_matches.is_empty() {
+            return self.display_page();
+        }
+        match key {
+            Event::Key(key) if key.code == KeyCode::Enter => self.walk_matches(),
+            _ => self.jump_to_match(),
+        }
+    }
 
-            print!("\nEnter command: ");
+    fn walk_matches(&mut self) -> io::Result<()> {
+        loop {
+            let line = self.tab().search_matches[self.tab().search_index];
+            let position = line.saturating_sub(Self::viewport_height()? / 2);
+            self.tab_mut().scroll_position = position.min(self.max_scroll()?);
+            self.display_page()?;
+            execute!(io::stdout(), SetForegroundColor(self.theme.hint))?;
+            print!(
+                "Match {}/{} - Enter: next, Backspace: previous, Esc: done",
+                self.tab().search_index + 1,
+                self.tab().search_matches.len()
+            );
+            execute!(io::stdout(), ResetColor)?;
             io::stdout().flush()?;
 
-            let mut input = String::new();
-            io::stdin().read_line(&mut input)?;
-            let input = input.trim();
-
-            if input == "q" {
-                break;
-            } else if input.starts_with('d') {
-                if let Some(num) = input.split_whitespace().nth(1) {
-                    if let Ok(index) = num.parse::<usize>() {
-                        if index > 0 && index <= self.bookmarks.len() {
-                            self.bookmarks.remove(index - 1);
-                            self.save_bookmarks()?;
-                            println!("Bookmark deleted!");
-                            std::thread::sleep(std::time::Duration::from_secs(1));
-                        }
+            enable_raw_mode()?;
+            let key = read();
+            disable_raw_mode()?;
+            let count = self.tab().search_matches.len();
+            let tab = self.tab_mut();
+            match key? {
+                Event::Key(key) if key.kind != KeyEventKind::Press => {}
+                Event::Key(key) => match key.code {
+                    KeyCode::Enter | KeyCode::Down => {
+                        tab.search_index = (tab.search_index + 1) % count
                     }
-                }
-            } else if let Ok(index) = input.parse::<usize>() {
-                if index > 0 && index <= self.bookmarks.len() {
-                    let url = self.bookmarks[index - 1].url.clone();
-                    self.navigate(&url)?;
-                    break;
-                }
+                    KeyCode::Backspace | KeyCode::Up => {
+                        tab.search_index = tab.search_index.checked_sub(1).unwrap_or(count - 1)
+                    }
+                    KeyCode::Esc | KeyCode::Char('q') => break,
+                    _ => {}
+                },
+                _ => {}
             }
         }
+        println!();
         Ok(())
     }
 
-    fn show_history(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    fn search_all(&mut self, query: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let pattern = match Pattern::parse(query, self.case_sensitive) {
+            Ok(pattern) => pattern,
+            Err(e) => {
+                println!("Invalid pattern: {}", e);
+                return Ok(());
+            }
+        };
+
+        let mut hits: Vec<SearchHit> = Vec::new();
+        for (index, tab) in self.tabs.iter().enumerate() {
+            hits.extend(search_hits(&tab.page_content, &pattern, || {
+                HitSource::Tab(index)
+            }));
+        }
+        let width = self.target_width();
+        for entry in &self.cache {
+            let open = self
+                .tabs
+                .iter()
+                .any(|tab| tab.current_url.as_deref() == Some(&entry.url));
+            let content_type = entry
+                .headers
+                .get(CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok());
+            let content_type = content_type.unwrap_or("");
+            if open || !content_type.contains("text/html") {
+                continue;
+            }
+            let html = Self::decode_body(&entry.body, content_type);
+            let (content, _) = Self::render_html(&html, width);
+            hits.extend(search_hits(&content, &pattern, || {
+                HitSource::Cached(entry.url.clone())
+            }));
+        }
+
+        let terminal_width = crossterm::terminal::size().map_or(80, |(width, _)| width as usize);
         loop {
             execute!(io::stdout(), Clear(ClearType::All))?;
-
             execute!(
                 io::stdout(),
-                SetBackgroundColor(Color::DarkBlue),
-                SetForegroundColor(Color::White)
+                SetBackgroundColor(Color::Yellow),
+                SetForegroundColor(Color::Black)
             )?;
-            println!(" Browsing History ");
+            println!(" Search All Tabs: \"{}\" ", query);
             execute!(io::stdout(), ResetColor)?;
             println!();
 
-            for (i, url) in self.history.iter().enumerate() {
+            for (i, hit) in hits.iter().enumerate() {
+                let source = match &hit.source {
+                    HitSource::Tab(index) => {
+                        let tab = &self.tabs[*index];
+                        let title = tab.title.as_deref().or(tab.current_url.as_deref());
+                        format!("tab {}: {}", index + 1, title.unwrap_or("(empty)"))
+                    }
+                    HitSource::Cached(url) => format!("cached: {}", url),
+                };
                 execute!(io::stdout(), SetForegroundColor(Color::DarkGrey))?;
                 print!(" {}. ", i + 1);
-
                 execute!(io::stdout(), SetForegroundColor(Color::Blue))?;
-                println!("{}", url);
+                let source = fit_to_width(&source, terminal_width / 2);
+                println!("{} (line {})", source.trim_end(), hit.line + 1);
+                execute!(io::stdout(), ResetColor)?;
+                print!("    ");
+                let text: String = hit
+                    .text
+                    .chars()
+                    .take(terminal_width.saturating_sub(4))
+                    .collect();
+                Self::print_highlighted(&text, &pattern, Color::Reset, Color::Yellow)?;
+                println!();
+            }
+            if hits.is_empty() {
+                execute!(io::stdout(), SetForegroundColor(Color::Red))?;
+                println!("No matches in open tabs or cached pages.");
             }
 
             execute!(io::stdout(), ResetColor)?;
             println!("\nCommands:");
-            println!("number - Go to URL from history");
+            println!("number - Go to match");
             println!("q - Return to browser");
 
             print!("\nEnter command: ");
@@ -304,201 +4758,667 @@ impl Browser {
             let input = input.trim();
 
             if input == "q" {
-                break;
-            } else if let Ok(index) = input.parse::<usize>() {
-                if index > 0 && index <= self.history.len() {
-                    let url = self.history[index - 1].clone();
-                    self.navigate(&url)?;
-                    break;
-                }
+                return Ok(self.display_page()?);
+            } else if let Some(hit) = input
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| n.checked_sub(1))
+                .and_then(|i| hits.get(i))
+            {
+                let line = match &hit.source {
+                    HitSource::Tab(index) => {
+                        self.active_tab = *index;
+                        Some(hit.line)
+                    }
+                    HitSource::Cached(url) => {
+                        let url = url.clone();
+                        self.navigate(&url)?;
+                        self.tab()
+                            .page_content
+                            .lines()
+                            .position(|line| line.trim() == hit.text)
+                    }
+                };
+                return Ok(self.show_search_hit(query, line)?);
             }
         }
-        Ok(())
     }
 
-    fn view_page_source(&self) -> io::Result<()> {
-        execute!(io::stdout(), Clear(ClearType::All))?;
-        println!("Page Source:");
-        if let Some(url) = &self.current_url {
-            let response = self.client.get(url).send().ok();
-            if let Some(resp) = response {
-                if let Ok(text) = resp.text() {
-                    println!("{}", text);
-                    return Ok(());
-                }
-            }
+    fn show_search_hit(&mut self, query: &str, line: Option<usize>) -> io::Result<()> {
+        let pattern = match Pattern::parse(query, self.case_sensitive) {
+            Ok(pattern) => pattern,
+            Err(_) => return self.display_page(),
+        };
+        let matches = matching_lines(&self.tab().page_content, &pattern);
+        if matches.is_empty() {
+            return self.display_page();
         }
-        println!("Unable to fetch page source");
-        Ok(())
+        let tab = self.tab_mut();
+        tab.search_index = line
+            .and_then(|line| matches.iter().position(|&m| m == line))
+            .unwrap_or(0);
+        tab.search_matches = matches;
+        tab.search_pattern = Some(pattern);
+        self.jump_to_match()
     }
 
-    fn download_page(&self, filename: &str) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(url) = &self.current_url {
-            let response = self.client.get(url).send()?;
-            let content = response.bytes()?;
-            std::fs::write(filename, content)?;
-            println!("Page downloaded to: {}", filename);
+    fn clear_search(&mut self) -> io::Result<()> {
+        let tab = self.tab_mut();
+        tab.search_pattern = None;
+        tab.search_matches.clear();
+        tab.search_index = 0;
+        self.display_page()
+    }
+
+    fn jump_to_match(&mut self) -> io::Result<()> {
+        let line = self.tab().search_matches[self.tab().search_index];
+        self.tab_mut().scroll_position = line.saturating_sub(Self::viewport_height()? / 3);
+        self.display_page()
+    }
+
+    fn next_match(&mut self) -> io::Result<()> {
+        if self.tab().search_matches.is_empty() {
+            println!("No active search.");
+            return Ok(());
         }
-        Ok(())
+        let tab = self.tab_mut();
+        tab.search_index = (tab.search_index + 1) % tab.search_matches.len();
+        self.jump_to_match()
+    }
+
+    fn previous_match(&mut self) -> io::Result<()> {
+        if self.tab().search_matches.is_empty() {
+            println!("No active search.");
+            return Ok(());
+        }
+        let tab = self.tab_mut();
+        tab.search_index = tab
+            .search_index
+            .checked_sub(1)
+            .unwrap_or(tab.search_matches.len() - 1);
+        self.jump_to_match()
     }
 
-    fn search_in_page(&self, query: &str) -> io::Result<()> {
+    fn toggle_raw_mode(&mut self) -> io::Result<()> {
         execute!(io::stdout(), Clear(ClearType::All))?;
+        println!("{}", self.tab().page_content);
+        println!("\nPress any key to return to normal mode...");
+        io::stdout().flush()?;
 
-        execute!(
-            io::stdout(),
-            SetBackgroundColor(Color::Yellow),
-            SetForegroundColor(Color::Black)
-        )?;
-        println!(" Search Results: \"{}\" ", query);
-        execute!(io::stdout(), ResetColor)?;
-        println!();
+        enable_raw_mode()?;
+        let _ = read()?;
+        disable_raw_mode()?;
+
+        self.display_page()?;
+        Ok(())
+    }
+}
 
-        let lines: Vec<&str> = self.page_content.lines().collect();
-        let mut found = false;
+impl Context for Browser {
+    fn complete(&self, line: &str) -> Vec<String> {
+        self.completions(line)
+    }
 
-        for (i, line) in lines.iter().enumerate() {
-            if line.to_lowercase().contains(&query.to_lowercase()) {
-                found = true;
+    fn resized(&mut self) -> io::Result<()> {
+        self.display_page()
+    }
 
-                execute!(io::stdout(), SetForegroundColor(Color::DarkGrey))?;
-                print!("{:4} │ ", i + 1);
+    fn mouse(&mut self, event: MouseEvent) -> io::Result<()> {
+        match event.kind {
+            MouseEventKind::ScrollUp => self.scroll_by(-MOUSE_SCROLL_LINES),
+            MouseEventKind::ScrollDown => self.scroll_by(MOUSE_SCROLL_LINES),
+            _ => {
+                if let Err(e) = self.click(event.column as usize, event.row as usize) {
+                    self.report_error(&e)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
 
-                let lower_line = line.to_lowercase();
-                let lower_query = query.to_lowercase();
-                let mut last_pos = 0;
+fn matching_lines(content: &str, pattern: &Pattern) -> Vec<usize> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| pattern.is_match(line))
+        .map(|(i, _)| i)
+        .collect()
+}
 
-                for (start, _) in lower_line.match_indices(&lower_query) {
-                    execute!(io::stdout(), ResetColor)?;
-                    print!("{}", &line[last_pos..start]);
+fn search_hits(content: &str, pattern: &Pattern, source: impl Fn() -> HitSource) -> Vec<SearchHit> {
+    let lines: Vec<&str> = content.lines().collect();
+    matching_lines(content, pattern)
+        .into_iter()
+        .map(|line| SearchHit {
+            source: source(),
+            line,
+            text: lines[line].trim().to_string(),
+        })
+        .collect()
+}
 
-                    execute!(
-                        io::stdout(),
-                        SetBackgroundColor(Color::Yellow),
-                        SetForegroundColor(Color::Black)
-                    )?;
-                    print!("{}", &line[start..start + query.len()]);
+fn fit_to_width(text: &str, width: usize) -> String {
+    let text: String = text.chars().take(width).collect();
+    let padding = " ".repeat(width.saturating_sub(text.chars().count()));
+    format!("{}{}", text, padding)
+}
 
-                    last_pos = start + query.len();
-                }
+fn scroll_label(position: usize, max_scroll: usize) -> String {
+    if max_scroll == 0 {
+        "All".to_string()
+    } else if position == 0 {
+        "Top".to_string()
+    } else if position >= max_scroll {
+        "Bot".to_string()
+    } else {
+        format!("{}%", position * 100 / max_scroll)
+    }
+}
 
-                execute!(io::stdout(), ResetColor)?;
-                println!("{}", &line[last_pos..]);
+fn link_markers(line: &str) -> Vec<(usize, usize)> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut markers = Vec::new();
+    for (start, _) in chars.iter().enumerate().filter(|(_, c)| **c == '[') {
+        let digits: String = chars[start + 1..]
+            .iter()
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+        let end = start + 1 + digits.len();
+        if chars.get(end) == Some(&']') {
+            if let Ok(number) = digits.parse() {
+                markers.push((end + 1, number));
+            }
+        }
+    }
+    markers
+}
+
+/// Returns the link whose marker or text covers the character at `offset`.
+/// The text is the bracketed `[text]` before the marker, or else the word
+/// it is attached to.
+fn link_at(line: &str, offset: usize) -> Option<usize> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut previous_end = 0;
+    for (end, link) in link_markers(line) {
+        let marker_start = chars[..end].iter().rposition(|c| *c == '[')?;
+        let text = &chars[previous_end..marker_start];
+        let start = if text.last() == Some(&']') {
+            text.iter().rposition(|c| *c == '[').unwrap_or(text.len())
+        } else {
+            text.iter()
+                .rposition(|c| c.is_whitespace())
+                .map_or(0, |space| space + 1)
+        };
+        if (previous_end + start..end).contains(&offset) {
+            return Some(link);
+        }
+        previous_end = end;
+    }
+    None
+}
+
+fn fix_scheme(url: &str) -> Option<String> {
+    let scheme_len = url.find(|c: char| !c.is_ascii_alphabetic())?;
+    let separator_len = url[scheme_len..]
+        .find(|c: char| !matches!(c, ':' | ';' | '/'))
+        .unwrap_or(url.len() - scheme_len);
+    if !url[scheme_len..scheme_len + separator_len].contains('/') {
+        return None;
+    }
+
+    let mut squeezed = url[..scheme_len].to_ascii_lowercase().into_bytes();
+    squeezed.dedup();
+    let scheme = match squeezed.as_slice() {
+        b"htp" | b"tp" => "http",
+        b"htps" | b"tps" => "https",
+        _ => return None,
+    };
+    let fixed = format!("{}://{}", scheme, &url[scheme_len + separator_len..]);
+    Some(fixed).filter(|fixed| fixed != url)
+}
+
+fn fragment_of(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    url.fragment()
+        .filter(|fragment| !fragment.is_empty())
+        .map(String::from)
+}
+
+fn remove_duplicates(bookmarks: &mut Vec<Bookmark>) -> Vec<Bookmark> {
+    let mut kept: Vec<Bookmark> = Vec::new();
+    let mut removed = Vec::new();
+    for bookmark in bookmarks.drain(..) {
+        match kept
+            .iter_mut()
+            .find(|existing| existing.url == bookmark.url)
+        {
+            Some(existing) => {
+                existing.key = existing.key.or(bookmark.key);
+                removed.push(bookmark);
             }
+            None => kept.push(bookmark),
         }
+    }
+    *bookmarks = kept;
+    removed
+}
 
-        if !found {
-            execute!(io::stdout(), SetForegroundColor(Color::Red))?;
-            println!("No matches found.");
+fn is_cancel_key(key: KeyEvent) -> bool {
+    key.kind == KeyEventKind::Press
+        && (key.code == KeyCode::Esc
+            || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)))
+}
+
+fn quickmark_key(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(key), None) if key.is_ascii_alphanumeric() => Some(key),
+        _ => None,
+    }
+}
+
+fn visible_len(line: &str) -> usize {
+    let mut len = 0;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            len += 1;
         }
+    }
+    len
+}
 
-        execute!(io::stdout(), ResetColor)?;
-        println!("\nPress any key to return...");
-        Ok(())
+fn hard_wrap(text: &str, width: usize) -> String {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        if line.contains('\x1b') || line.chars().count() <= width {
+            lines.push(line.to_string());
+            continue;
+        }
+        let mut rest: Vec<char> = line.chars().collect();
+        while rest.len() > width {
+            let split = rest[width / 2..=width]
+                .iter()
+                .rposition(|c| c.is_whitespace())
+                .map_or(width, |i| i + width / 2 + 1);
+            lines.push(
+                rest[..split]
+                    .iter()
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string(),
+            );
+            rest.drain(..split);
+        }
+        lines.push(rest.into_iter().collect());
+    }
+    lines.join("\n")
+}
+
+fn gutter_width(line_count: usize, show_numbers: bool) -> usize {
+    if !show_numbers {
+        return 0;
     }
+    let digits = line_count.max(1).to_string().len().max(MIN_GUTTER_DIGITS);
+    digits + GUTTER_SEPARATOR.chars().count()
+}
 
-    fn toggle_raw_mode(&self) -> io::Result<()> {
-        execute!(io::stdout(), Clear(ClearType::All))?;
-        println!("{}", self.page_content);
-        println!("\nPress any key to return to normal mode...");
-        io::stdout().flush()?;
+fn line_rows(line: &str, width: usize, gutter: usize) -> usize {
+    (gutter + visible_len(line)).div_ceil(width.max(1)).max(1)
+}
 
-        enable_raw_mode()?;
-        let _ = read()?;
-        disable_raw_mode()?;
+fn format_elapsed(elapsed: Duration) -> String {
+    if elapsed.as_secs() == 0 {
+        format!("{} ms", elapsed.as_millis())
+    } else {
+        format!("{:.1}s", elapsed.as_secs_f64())
+    }
+}
 
-        self.display_page()?;
-        Ok(())
+fn fetch_image(request: RequestBuilder) -> Option<String> {
+    let response = request.send().ok()?;
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .filter(|content_type| content_type.starts_with("image/"))?
+        .to_string();
+    if !response.status().is_success() {
+        return None;
+    }
+    let body = read_limited(response, MAX_SNAPSHOT_IMAGE_BYTES).ok()?;
+    Some(format!(
+        "data:{};base64,{}",
+        content_type,
+        STANDARD.encode(&body)
+    ))
+}
+
+fn read_limited(response: Response, limit: u64) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let too_large = || {
+        format!(
+            "Response exceeds size limit ({}), use download to stream to disk",
+            format_size(limit)
+        )
+    };
+    if limit > 0
+        && response
+            .content_length()
+            .is_some_and(|length| length > limit)
+    {
+        return Err(too_large().into());
+    }
+    let mut body = Vec::new();
+    let cap = if limit == 0 { u64::MAX } else { limit + 1 };
+    response.take(cap).read_to_end(&mut body)?;
+    if limit > 0 && body.len() as u64 > limit {
+        return Err(too_large().into());
+    }
+    Ok(body)
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
     }
 }
 
+fn dump(url: &str, json: bool, log_requests: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let mut browser = Browser::new();
+    browser.headless = true;
+    browser.log_requests = log_requests;
+    browser.load(&Browser::normalize_url(url))?;
+    let tab = browser.tab();
+    match &tab.json_tree {
+        Some(tree) if json => println!("{}", tree.pretty()),
+        _ => println!("{}", tab.page_content),
+    }
+    Ok(())
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().collect();
+    let log_requests = args.iter().any(|arg| arg == LOG_REQUESTS_FLAG);
+    if let Some(i) = args.iter().position(|arg| arg == DUMP_FLAG) {
+        let json = args.iter().any(|arg| arg == JSON_FLAG);
+        let result = match args.get(i + 1) {
+            Some(url) => dump(url, json, log_requests),
+            None => Err("--dump needs a URL".into()),
+        };
+        if let Err(e) = result {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let mut browser = Browser::new();
+    if std::env::args().any(|arg| arg == "--private") {
+        browser.set_private(true);
+    }
+    browser.log_requests = log_requests;
+    let profile = args
+        .iter()
+        .position(|arg| arg == PROFILE_FLAG)
+        .and_then(|i| args.get(i + 1));
+    if let Some(name) = profile {
+        if let Err(e) = browser.switch_profile(Some(name)) {
+            browser.report_error(&e)?;
+        }
+    }
+    let mut editor = LineEditor::new(browser.state_file(COMMAND_HISTORY_FILE));
+    if browser.options.accept_invalid_certs {
+        println!(
+            "Warning: TLS certificate validation is disabled. Only use this for trusted hosts."
+        );
+    }
     println!("Welcome to the Rust Web Browser!");
     println!("Type 'h' for help.");
 
-    loop {
-        print!("\nCommand: ");
+    let mut restored = false;
+    let saved_tabs = browser.load_session().tabs.len();
+    if saved_tabs > 0 && !browser.private && io::stdin().is_terminal() {
+        print!("Restore previous session ({} tabs)? [Y/n] ", saved_tabs);
         io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if !answer.trim().eq_ignore_ascii_case("n") {
+            restored = browser.restore_session()?;
+        }
+    }
 
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-
-        match input.trim() {
-            "q" | "quit" => break,
-            "h" | "help" => {
-                println!("Commands:");
-                println!("g URL      - Go to URL");
-                println!("b         - Show bookmarks");
-                println!("a TITLE   - Add current page to bookmarks");
-                println!("h         - Show this help");
-                println!("history   - Show history");
-                println!("r         - Reload current page");
-                println!("source    - View page source");
-                println!("raw       - Toggle raw mode view");
-                println!("download FILENAME - Download current page");
-                println!("search QUERY - Search in current page");
-                println!("w         - Scroll up");
-                println!("s         - Scroll down");
-                println!("q         - Quit");
-            }
-            "w" => {
-                if browser.scroll_position >= 5 {
-                    browser.scroll_position -= 5;
-                } else {
-                    browser.scroll_position = 0;
-                }
-                browser.display_page()?;
+    if !restored {
+        if let Some(home) = browser.config.home_url.clone() {
+            if let Err(e) = browser.navigate(&home) {
+                browser.report_error(&e)?;
             }
-            "s" => {
-                let lines = browser.page_content.lines().count();
-                let terminal_height = crossterm::terminal::size()?.1 as usize - 7;
-                let max_scroll = if lines > terminal_height {
-                    lines - terminal_height
-                } else {
-                    0
-                };
+        }
+    }
 
-                browser.scroll_position = std::cmp::min(browser.scroll_position + 5, max_scroll);
-                browser.display_page()?;
+    loop {
+        println!();
+        editor.set_private(browser.private);
+        editor.set_mouse(browser.config.mouse);
+        editor.set_history_file(browser.state_file(COMMAND_HISTORY_FILE));
+        let input = match editor.read_line("Command: ", &mut browser)? {
+            Some(input) => input,
+            None => {
+                browser.save_cookies()?;
+                browser.save_session()?;
+                break;
             }
-            "b" => browser.show_bookmarks()?,
-            "history" => browser.show_history()?,
-            "r" => {
-                if let Some(url) = browser.current_url.clone() {
-                    browser.navigate(&url)?;
+        };
+
+        let input = input.trim();
+        if input == ":" {
+            match commands::palette()? {
+                Some(command) => {
+                    let mut argument = String::new();
+                    if !command.args.is_empty() {
+                        print!("{}: ", command.usage());
+                        io::stdout().flush()?;
+                        io::stdin().read_line(&mut argument)?;
+                    }
+                    commands::run(&mut browser, command, argument.trim())?;
                 }
+                None => browser.display_page()?,
             }
-            input if input.starts_with("g ") => {
-                let url = input[2..].trim();
-                if let Err(e) = browser.navigate(url) {
-                    println!("Error: {}", e);
-                }
+        } else if let Some((action, argument)) = browser.keymap.resolve(input) {
+            if let Err(e) = browser.perform(action, argument) {
+                browser.report_error(&e)?;
             }
-            input if input.starts_with("a ") => {
-                let title = input[2..].trim();
-                if let Err(e) = browser.add_bookmark(title) {
-                    println!("Error adding bookmark: {}", e);
-                }
+        } else {
+            let (verb, argument) = commands::parse(input);
+            match commands::find(verb) {
+                Some(command) => commands::run(&mut browser, command, argument)?,
+                None => println!("Unknown command. Press 'h' for help."),
             }
-            "source" => browser.view_page_source()?,
-            "raw" => browser.toggle_raw_mode()?,
+        }
+        if browser.quitting {
+            browser.save_cookies()?;
+            browser.save_session()?;
+            break;
+        }
+    }
 
-            input if input.starts_with("download ") => {
-                let filename = input[9..].trim();
-                if let Err(e) = browser.download_page(filename) {
-                    println!("Error downloading page: {}", e);
-                }
-            }
+    Ok(())
+}
 
-            input if input.starts_with("search ") => {
-                let query = input[7..].trim();
-                browser.search_in_page(query)?;
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            _ => println!("Unknown command. Press 'h' for help."),
-        }
+    #[test]
+    fn fit_to_width_truncates_on_tiny_terminals() {
+        assert_eq!(fit_to_width(" Rust Web Browser ", 4), " Rus");
+        assert_eq!(fit_to_width(" Lines: 10 ", 0), "");
     }
 
-    Ok(())
+    #[test]
+    fn fit_to_width_pads_short_text() {
+        assert_eq!(fit_to_width("ab", 5), "ab   ");
+    }
+
+    #[test]
+    fn scroll_label_matches_less() {
+        assert_eq!(scroll_label(0, 0), "All");
+        assert_eq!(scroll_label(0, 40), "Top");
+        assert_eq!(scroll_label(18, 40), "45%");
+        assert_eq!(scroll_label(40, 40), "Bot");
+    }
+
+    #[test]
+    fn link_markers_reports_marker_ends() {
+        assert_eq!(
+            link_markers("See docs[1] and [é][12]."),
+            vec![(11, 1), (23, 12)]
+        );
+        assert!(link_markers("[x] [] [3").is_empty());
+    }
+
+    #[test]
+    fn link_at_covers_marker_and_text_only() {
+        let line = "See the docs[1] and [a guide][2].";
+        assert_eq!(link_at(line, 8), Some(1));
+        assert_eq!(link_at(line, 14), Some(1));
+        assert_eq!(link_at(line, 22), Some(2));
+        assert_eq!(link_at(line, 0), None);
+        assert_eq!(link_at(line, 16), None);
+        assert_eq!(link_at(line, 40), None);
+    }
+
+    #[test]
+    fn fix_scheme_corrects_typos_only() {
+        let fixed = fix_scheme;
+        assert_eq!(
+            fixed("htttps://example.com"),
+            Some("https://example.com".to_string())
+        );
+        assert_eq!(
+            fixed("https//example.com/a"),
+            Some("https://example.com/a".to_string())
+        );
+        assert_eq!(
+            fixed("http:/example.com"),
+            Some("http://example.com".to_string())
+        );
+        assert_eq!(
+            fixed("hhtps:///example.com"),
+            Some("https://example.com".to_string())
+        );
+        assert_eq!(fixed("https://example.com"), None);
+        assert_eq!(fixed("httpbin.org/get"), None);
+        assert_eq!(fixed("ftp://example.com"), None);
+    }
+
+    #[test]
+    fn hard_wrap_splits_long_lines() {
+        assert_eq!(hard_wrap("short\nline", 10), "short\nline");
+        assert_eq!(
+            hard_wrap(&"x".repeat(25), 10),
+            "xxxxxxxxxx\nxxxxxxxxxx\nxxxxx"
+        );
+        assert_eq!(
+            hard_wrap("alpha beta gamma delta", 12),
+            "alpha beta\ngamma delta"
+        );
+    }
+
+    #[test]
+    fn line_rows_counts_wrapped_rows() {
+        assert_eq!(line_rows("", 80, 7), 1);
+        assert_eq!(line_rows(&"x".repeat(73), 80, 7), 1);
+        assert_eq!(line_rows(&"x".repeat(74), 80, 7), 2);
+        assert_eq!(line_rows("\x1b[38;2;1;2;3mx\x1b[0m", 8, 7), 1);
+        assert_eq!(line_rows(&"x".repeat(80), 80, 0), 1);
+    }
+
+    #[test]
+    fn duplicates_keep_first_title_and_quickmark() {
+        let bookmark = |title: &str, url: &str, key| Bookmark {
+            title: title.to_string(),
+            url: url.to_string(),
+            folder: None,
+            key,
+        };
+        let mut bookmarks = vec![
+            bookmark("First", "https://a.example/", None),
+            bookmark("Other", "https://b.example/", None),
+            bookmark("Second", "https://a.example/", Some('a')),
+        ];
+        let removed = remove_duplicates(&mut bookmarks);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].title, "Second");
+        assert_eq!(bookmarks.len(), 2);
+        assert_eq!(bookmarks[0].title, "First");
+        assert_eq!(bookmarks[0].key, Some('a'));
+    }
+
+    #[test]
+    fn client_decodes_gzip_bodies() {
+        use flate2::write::GzEncoder;
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"<p>Hello, gzip</p>").unwrap();
+        let body = encoder.finish().unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            stream.write_all(head.as_bytes()).unwrap();
+            stream.write_all(&body).unwrap();
+        });
+
+        let options = ClientOptions {
+            timeout: Duration::from_secs(5),
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+            cookies: Arc::new(CookieStoreMutex::new(CookieStore::default())),
+            redirect_count: Arc::new(AtomicUsize::new(0)),
+            proxies: Proxies::default(),
+            accept_invalid_certs: false,
+            user_agent: "test".to_string(),
+            blocklist: Arc::new(Blocklist::default()),
+            doh: false,
+            resolved: HashMap::new(),
+        };
+        let text = options
+            .build()
+            .get(format!("http://{}/", address))
+            .send()
+            .unwrap()
+            .text()
+            .unwrap();
+        server.join().unwrap();
+        assert_eq!(text, "<p>Hello, gzip</p>");
+    }
+
+    #[test]
+    fn gutter_grows_with_line_count() {
+        assert_eq!(gutter_width(120, true), 7);
+        assert_eq!(gutter_width(20_000, true), 8);
+        assert_eq!(gutter_width(20_000, false), 0);
+    }
 }
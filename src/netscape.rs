@@ -0,0 +1,111 @@
+use crate::dom::{self, text_of};
+use crate::Bookmark;
+use markup5ever_rcdom::Handle;
+use std::collections::BTreeMap;
+
+pub fn export(bookmarks: &[Bookmark]) -> String {
+    let mut folders: BTreeMap<Option<&str>, Vec<&Bookmark>> = BTreeMap::new();
+    for bookmark in bookmarks {
+        folders
+            .entry(bookmark.folder.as_deref())
+            .or_default()
+            .push(bookmark);
+    }
+
+    let mut html = String::from(
+        "<!DOCTYPE NETSCAPE-Bookmark-file-1>\n\
+         <META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\n\
+         <TITLE>Bookmarks</TITLE>\n\
+         <H1>Bookmarks</H1>\n\
+         <DL><p>\n",
+    );
+
+    for (folder, bookmarks) in folders {
+        let indent = match folder {
+            Some(folder) => {
+                html.push_str(&format!(
+                    "    <DT><H3>{}</H3>\n    <DL><p>\n",
+                    escape(folder)
+                ));
+                "        "
+            }
+            None => "    ",
+        };
+        for bookmark in bookmarks {
+            html.push_str(&format!(
+                "{}<DT><A HREF=\"{}\">{}</A>\n",
+                indent,
+                escape(&bookmark.url),
+                escape(&bookmark.title)
+            ));
+        }
+        if folder.is_some() {
+            html.push_str("    </DL><p>\n");
+        }
+    }
+
+    html.push_str("</DL><p>\n");
+    html
+}
+
+pub fn import(html: &str) -> Vec<Bookmark> {
+    let dom = dom::parse(html);
+
+    let mut bookmarks = Vec::new();
+    collect(&dom.document, &mut Vec::new(), &mut bookmarks);
+    bookmarks
+}
+
+fn collect(node: &Handle, path: &mut Vec<String>, bookmarks: &mut Vec<Bookmark>) -> Option<String> {
+    let mut pending_folder = None;
+
+    for child in node.children.borrow().iter() {
+        let name = match dom::tag_name(child) {
+            Some(name) => name,
+            None => continue,
+        };
+
+        match name.as_str() {
+            "h3" => pending_folder = Some(text_of(child)),
+            "dl" => {
+                let folder = pending_folder.take();
+                if let Some(folder) = &folder {
+                    path.push(folder.clone());
+                }
+                collect(child, path, bookmarks);
+                if folder.is_some() {
+                    path.pop();
+                }
+            }
+            "a" => {
+                if let Some(url) = dom::attr(child, "href") {
+                    let title = text_of(child);
+                    bookmarks.push(Bookmark {
+                        title: if title.is_empty() { url.clone() } else { title },
+                        url,
+                        folder: if path.is_empty() {
+                            None
+                        } else {
+                            Some(path.join("/"))
+                        },
+                        key: None,
+                    });
+                }
+            }
+            _ => {
+                if let Some(folder) = collect(child, path, bookmarks) {
+                    pending_folder = Some(folder);
+                }
+            }
+        }
+    }
+
+    pending_folder
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,177 @@
+use crossterm::{
+    cursor,
+    event::{read, Event, KeyCode, KeyEventKind, KeyModifiers},
+    execute,
+    style::{Color, ResetColor, SetBackgroundColor, SetForegroundColor},
+    terminal::{self, disable_raw_mode, enable_raw_mode, Clear, ClearType},
+};
+use std::io::{self, Write};
+
+pub struct Suggestion {
+    pub title: String,
+    pub url: String,
+    pub bookmarked: bool,
+}
+
+pub fn run(suggestions: &[Suggestion]) -> io::Result<Option<String>> {
+    enable_raw_mode()?;
+    let picked = edit(suggestions);
+    disable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        ResetColor,
+        Clear(ClearType::All),
+        cursor::MoveTo(0, 0)
+    )?;
+    picked
+}
+
+fn edit(suggestions: &[Suggestion]) -> io::Result<Option<String>> {
+    let mut query: Vec<char> = Vec::new();
+    let mut cursor = 0;
+    let mut selected: Option<usize> = None;
+
+    loop {
+        let text: String = query.iter().collect();
+        let matches = matching(suggestions, &text);
+        let (width, height) = terminal::size()?;
+        let rows = (height as usize).saturating_sub(4).max(1);
+        selected = selected
+            .filter(|_| !matches.is_empty())
+            .map(|i| i.min(matches.len() - 1));
+
+        execute!(
+            io::stdout(),
+            Clear(ClearType::All),
+            cursor::MoveTo(0, 0),
+            SetBackgroundColor(Color::DarkBlue),
+            SetForegroundColor(Color::White)
+        )?;
+        print!(" Go to ");
+        execute!(io::stdout(), ResetColor, cursor::MoveTo(0, 1))?;
+        print!("> {}", text);
+
+        let first = selected.unwrap_or(0).saturating_sub(rows - 1);
+        for (row, suggestion) in matches.iter().enumerate().skip(first).take(rows) {
+            execute!(io::stdout(), cursor::MoveTo(0, (row - first + 3) as u16))?;
+            if selected == Some(row) {
+                execute!(
+                    io::stdout(),
+                    SetBackgroundColor(Color::DarkBlue),
+                    SetForegroundColor(Color::White)
+                )?;
+            } else {
+                execute!(io::stdout(), SetForegroundColor(Color::Blue))?;
+            }
+            let marker = if suggestion.bookmarked { '★' } else { ' ' };
+            let line = format!(" {} {}  {}", marker, suggestion.url, suggestion.title);
+            print!("{}", line.chars().take(width as usize).collect::<String>());
+            execute!(io::stdout(), ResetColor)?;
+        }
+        execute!(io::stdout(), cursor::MoveTo((cursor + 2) as u16, 1))?;
+        io::stdout().flush()?;
+
+        let key = match read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Ok(None),
+            KeyCode::Char('c') if control => return Ok(None),
+            KeyCode::Enter => {
+                let url = match selected {
+                    Some(i) => matches[i].url.clone(),
+                    None => text.trim().to_string(),
+                };
+                return Ok(Some(url).filter(|url| !url.is_empty()));
+            }
+            KeyCode::Up => selected = selected.and_then(|i| i.checked_sub(1)),
+            KeyCode::Down => selected = Some(selected.map_or(0, |i| i + 1)),
+            KeyCode::Tab => {
+                if let Some(i) = selected.or(Some(0).filter(|_| !matches.is_empty())) {
+                    query = matches[i].url.chars().collect();
+                    cursor = query.len();
+                    selected = None;
+                }
+            }
+            KeyCode::Left => cursor = cursor.saturating_sub(1),
+            KeyCode::Right => cursor = (cursor + 1).min(query.len()),
+            KeyCode::Home => cursor = 0,
+            KeyCode::End => cursor = query.len(),
+            KeyCode::Backspace if cursor > 0 => {
+                cursor -= 1;
+                query.remove(cursor);
+                selected = None;
+            }
+            KeyCode::Delete if cursor < query.len() => {
+                query.remove(cursor);
+                selected = None;
+            }
+            KeyCode::Char(c) if !control => {
+                query.insert(cursor, c);
+                cursor += 1;
+                selected = None;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn matching<'a>(suggestions: &'a [Suggestion], query: &str) -> Vec<&'a Suggestion> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return suggestions.iter().collect();
+    }
+    let mut matches: Vec<(usize, &Suggestion)> = suggestions
+        .iter()
+        .filter_map(|suggestion| {
+            let url = suggestion.url.to_lowercase();
+            let bare = url.split_once("://").map_or(url.as_str(), |(_, rest)| rest);
+            let bare = bare.strip_prefix("www.").unwrap_or(bare);
+            let rank = if bare.starts_with(&query) {
+                0
+            } else if url.contains(&query) {
+                1
+            } else if suggestion.title.to_lowercase().contains(&query) {
+                2
+            } else {
+                return None;
+            };
+            Some((rank, suggestion))
+        })
+        .collect();
+    matches.sort_by_key(|(rank, _)| *rank);
+    matches
+        .into_iter()
+        .map(|(_, suggestion)| suggestion)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_prefix_matches_rank_first() {
+        let suggestion = |title: &str, url: &str| Suggestion {
+            title: title.to_string(),
+            url: url.to_string(),
+            bookmarked: false,
+        };
+        let suggestions = [
+            suggestion("Rust news", "https://news.example/rust"),
+            suggestion("", "https://www.rust-lang.org/"),
+            suggestion("Crates", "https://crates.io/"),
+        ];
+        let urls: Vec<&str> = matching(&suggestions, "RUST")
+            .iter()
+            .map(|suggestion| suggestion.url.as_str())
+            .collect();
+        assert_eq!(
+            urls,
+            ["https://www.rust-lang.org/", "https://news.example/rust"]
+        );
+        assert_eq!(matching(&suggestions, "").len(), 3);
+    }
+}
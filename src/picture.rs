@@ -0,0 +1,31 @@
+use crossterm::style::{Color, Stylize};
+use image::imageops::FilterType;
+use image::ImageResult;
+
+pub fn render(bytes: &[u8], width: usize, height: usize) -> ImageResult<String> {
+    let image = image::load_from_memory(bytes)?;
+    let width = width.max(1) as u32;
+    let height = (height.max(1) * 2) as u32;
+    let image = image.resize(width, height, FilterType::Triangle).to_rgb8();
+
+    let mut rows = Vec::new();
+    for y in (0..image.height()).step_by(2) {
+        let mut row = String::new();
+        for x in 0..image.width() {
+            let top = image.get_pixel(x, y);
+            let bottom = if y + 1 < image.height() {
+                image.get_pixel(x, y + 1)
+            } else {
+                top
+            };
+            let cell = '▀'.with(rgb(top.0)).on(rgb(bottom.0));
+            row.push_str(&cell.to_string());
+        }
+        rows.push(row);
+    }
+    Ok(rows.join("\n"))
+}
+
+fn rgb([r, g, b]: [u8; 3]) -> Color {
+    Color::Rgb { r, g, b }
+}
@@ -0,0 +1,156 @@
+use reqwest::blocking::RequestBuilder;
+use reqwest::header::HeaderMap;
+use reqwest::{StatusCode, Version};
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const MAX_AGE: Duration = Duration::from_secs(300);
+const MAX_BYTES: u64 = 2 * 1024 * 1024;
+const MAX_PAGES: usize = 16;
+
+pub struct Page {
+    pub url: String,
+    pub status: StatusCode,
+    pub version: Version,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+    pub elapsed: Duration,
+    fetched: Instant,
+}
+
+#[derive(Default)]
+pub struct Prefetcher {
+    pages: Arc<Mutex<HashMap<String, Option<Page>>>>,
+    active: Arc<AtomicUsize>,
+}
+
+impl Prefetcher {
+    pub fn wants(&self, url: &str) -> bool {
+        self.pages.lock().is_ok_and(|mut pages| {
+            prune(&mut pages);
+            !pages.contains_key(url)
+        })
+    }
+
+    pub fn start(&self, request: RequestBuilder, url: &str, limit: usize) -> bool {
+        if self.active.load(Ordering::Relaxed) >= limit {
+            return false;
+        }
+        match self.pages.lock() {
+            Ok(mut pages) => {
+                prune(&mut pages);
+                pages.insert(url.to_string(), None)
+            }
+            Err(_) => return false,
+        };
+        self.active.fetch_add(1, Ordering::Relaxed);
+
+        let pages = self.pages.clone();
+        let active = self.active.clone();
+        let url = url.to_string();
+        thread::spawn(move || {
+            let page = fetch(request);
+            if let Ok(mut pages) = pages.lock() {
+                match page {
+                    Some(page) => pages.insert(url, Some(page)),
+                    None => pages.remove(&url),
+                };
+            }
+            active.fetch_sub(1, Ordering::Relaxed);
+        });
+        true
+    }
+
+    pub fn take(&self, url: &str) -> Option<Page> {
+        let mut pages = self.pages.lock().ok()?;
+        match pages.get(url) {
+            Some(Some(page)) if page.fetched.elapsed() <= MAX_AGE => pages.remove(url)?,
+            _ => None,
+        }
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut pages) = self.pages.lock() {
+            pages.retain(|_, page| page.is_none());
+        }
+    }
+}
+
+fn prune(pages: &mut HashMap<String, Option<Page>>) {
+    pages.retain(|_, page| {
+        page.as_ref()
+            .is_none_or(|page| page.fetched.elapsed() <= MAX_AGE)
+    });
+    while pages.len() >= MAX_PAGES {
+        let oldest = pages
+            .iter()
+            .filter_map(|(url, page)| Some((url, page.as_ref()?.fetched)))
+            .min_by_key(|(_, fetched)| *fetched)
+            .map(|(url, _)| url.clone());
+        match oldest {
+            Some(url) => pages.remove(&url),
+            None => break,
+        };
+    }
+}
+
+fn fetch(request: RequestBuilder) -> Option<Page> {
+    let started = Instant::now();
+    let response = request.send().ok()?;
+    if !response.status().is_success()
+        || response.content_length().is_some_and(|len| len > MAX_BYTES)
+    {
+        return None;
+    }
+    let url = response.url().to_string();
+    let status = response.status();
+    let version = response.version();
+    let headers = response.headers().clone();
+    let mut body = Vec::new();
+    response.take(MAX_BYTES + 1).read_to_end(&mut body).ok()?;
+    if body.len() as u64 > MAX_BYTES {
+        return None;
+    }
+    Some(Page {
+        url,
+        status,
+        version,
+        headers,
+        body,
+        elapsed: started.elapsed(),
+        fetched: Instant::now(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prune_drops_expired_and_oldest_pages() {
+        let now = Instant::now();
+        let page = |age: u64| Page {
+            url: String::new(),
+            status: StatusCode::OK,
+            version: Version::HTTP_11,
+            headers: HeaderMap::new(),
+            body: Vec::new(),
+            elapsed: Duration::ZERO,
+            fetched: now - Duration::from_secs(age),
+        };
+        let mut pages = HashMap::new();
+        pages.insert("expired".to_string(), Some(page(MAX_AGE.as_secs() + 1)));
+        pages.insert("loading".to_string(), None);
+        for i in 0..MAX_PAGES {
+            pages.insert(format!("page{}", i), Some(page(i as u64 + 1)));
+        }
+        prune(&mut pages);
+        assert_eq!(pages.len(), MAX_PAGES - 1);
+        assert!(pages.contains_key("loading") && pages.contains_key("page0"));
+        assert!(!pages.contains_key("expired") && !pages.contains_key("page15"));
+    }
+}
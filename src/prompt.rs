@@ -0,0 +1,298 @@
+use crossterm::{
+    cursor,
+    event::{
+        read, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers,
+        MouseButton, MouseEvent, MouseEventKind,
+    },
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType},
+};
+use std::fs::{self, OpenOptions};
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
+
+pub const COMMAND_HISTORY_FILE: &str = "command_history";
+const MAX_COMMAND_HISTORY: usize = 500;
+const ADDRESS_BAR_COMMAND: &str = "go";
+const SECRET_COMMANDS: &[&str] = &["auth", "token"];
+
+pub trait Context {
+    fn complete(&self, line: &str) -> Vec<String>;
+    fn resized(&mut self) -> io::Result<()>;
+    fn mouse(&mut self, event: MouseEvent) -> io::Result<()>;
+}
+
+pub struct LineEditor {
+    path: PathBuf,
+    history: Vec<String>,
+    private: bool,
+    mouse: bool,
+}
+
+impl LineEditor {
+    pub fn new(path: PathBuf) -> Self {
+        LineEditor {
+            history: load_history(&path),
+            path,
+            private: false,
+            mouse: false,
+        }
+    }
+
+    pub fn set_history_file(&mut self, path: PathBuf) {
+        if path == self.path {
+            return;
+        }
+        self.history = load_history(&path);
+        self.path = path;
+    }
+
+    pub fn set_private(&mut self, private: bool) {
+        self.private = private;
+    }
+
+    pub fn set_mouse(&mut self, mouse: bool) {
+        self.mouse = mouse;
+    }
+
+    pub fn read_line(
+        &mut self,
+        prompt: &str,
+        context: &mut dyn Context,
+    ) -> io::Result<Option<String>> {
+        print!("{}", prompt);
+        io::stdout().flush()?;
+
+        let line = if io::stdin().is_terminal() && enable_raw_mode().is_ok() {
+            if self.mouse {
+                execute!(io::stdout(), EnableMouseCapture)?;
+            }
+            let line = self.edit(prompt, context);
+            if self.mouse {
+                execute!(io::stdout(), DisableMouseCapture)?;
+            }
+            disable_raw_mode()?;
+            println!();
+            line?
+        } else {
+            let mut input = String::new();
+            if io::stdin().read_line(&mut input)? == 0 {
+                None
+            } else {
+                Some(input.trim_end_matches(['\r', '\n']).to_string())
+            }
+        };
+
+        if let Some(line) = &line {
+            self.remember(line)?;
+        }
+        Ok(line)
+    }
+
+    fn edit(&self, prompt: &str, context: &mut dyn Context) -> io::Result<Option<String>> {
+        let mut buffer: Vec<char> = Vec::new();
+        let mut cursor = 0;
+        let mut recall = self.history.len();
+        let mut draft = String::new();
+
+        loop {
+            let key = match read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => key,
+                Event::Resize(_, _) => {
+                    suspend(|| context.resized())?;
+                    redraw(prompt, &buffer, cursor)?;
+                    continue;
+                }
+                Event::Mouse(mouse)
+                    if matches!(
+                        mouse.kind,
+                        MouseEventKind::ScrollUp
+                            | MouseEventKind::ScrollDown
+                            | MouseEventKind::Down(MouseButton::Left)
+                    ) =>
+                {
+                    suspend(|| context.mouse(mouse))?;
+                    redraw(prompt, &buffer, cursor)?;
+                    continue;
+                }
+                _ => continue,
+            };
+
+            let control = key.modifiers.contains(KeyModifiers::CONTROL);
+            match key.code {
+                KeyCode::Enter => break,
+                KeyCode::Char('j') if control => break,
+                KeyCode::Char('d') if control && buffer.is_empty() => return Ok(None),
+                KeyCode::Char('c') if control => {
+                    buffer.clear();
+                    break;
+                }
+                KeyCode::Char('l') if control && buffer.is_empty() => {
+                    buffer = ADDRESS_BAR_COMMAND.chars().collect();
+                    break;
+                }
+                KeyCode::Char('a') if control => cursor = 0,
+                KeyCode::Char('e') if control => cursor = buffer.len(),
+                KeyCode::Char(c) if !control => {
+                    buffer.insert(cursor, c);
+                    cursor += 1;
+                }
+                KeyCode::Tab if cursor == buffer.len() => {
+                    let line: String = buffer.iter().collect();
+                    let candidates = context.complete(&line);
+                    let prefix = common_prefix(&candidates);
+
+                    if candidates.len() == 1 {
+                        buffer = candidates[0].chars().collect();
+                        if !candidates[0].contains(' ') {
+                            buffer.push(' ');
+                        }
+                    } else if prefix.chars().count() > buffer.len() {
+                        buffer = prefix.chars().collect();
+                    } else if candidates.len() > 1 {
+                        print!("\r\n{}\r\n", candidates.join("  "));
+                    }
+                    cursor = buffer.len();
+                }
+                KeyCode::Home => cursor = 0,
+                KeyCode::End => cursor = buffer.len(),
+                KeyCode::Backspace if cursor > 0 => {
+                    cursor -= 1;
+                    buffer.remove(cursor);
+                }
+                KeyCode::Delete if cursor < buffer.len() => {
+                    buffer.remove(cursor);
+                }
+                KeyCode::Left => cursor = cursor.saturating_sub(1),
+                KeyCode::Right => cursor = (cursor + 1).min(buffer.len()),
+                KeyCode::Up if recall > 0 => {
+                    if recall == self.history.len() {
+                        draft = buffer.iter().collect();
+                    }
+                    recall -= 1;
+                    buffer = self.history[recall].chars().collect();
+                    cursor = buffer.len();
+                }
+                KeyCode::Down if recall < self.history.len() => {
+                    recall += 1;
+                    buffer = match self.history.get(recall) {
+                        Some(entry) => entry.chars().collect(),
+                        None => draft.chars().collect(),
+                    };
+                    cursor = buffer.len();
+                }
+                _ => continue,
+            }
+
+            redraw(prompt, &buffer, cursor)?;
+        }
+
+        Ok(Some(buffer.into_iter().collect()))
+    }
+
+    fn remember(&mut self, line: &str) -> io::Result<()> {
+        if self.private {
+            return Ok(());
+        }
+        let line = line.trim();
+        if line.is_empty() || self.history.last().map(String::as_str) == Some(line) {
+            return Ok(());
+        }
+        let verb = line.split_whitespace().next().unwrap_or_default();
+        if SECRET_COMMANDS.contains(&verb) {
+            return Ok(());
+        }
+
+        self.history.push(line.to_string());
+        if self.history.len() > MAX_COMMAND_HISTORY {
+            self.history.remove(0);
+        }
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", self.history.join("\n"))
+    }
+}
+
+pub fn read_secret(prompt: &str) -> io::Result<String> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+    if !io::stdin().is_terminal() || enable_raw_mode().is_err() {
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        return Ok(input.trim_end_matches(['\r', '\n']).to_string());
+    }
+
+    let mut secret = String::new();
+    let result = loop {
+        let key = match read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => key,
+            Ok(_) => continue,
+            Err(e) => break Err(e),
+        };
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Enter => break Ok(()),
+            KeyCode::Char('c') if control => {
+                secret.clear();
+                break Ok(());
+            }
+            KeyCode::Char(c) if !control => secret.push(c),
+            KeyCode::Backspace => {
+                secret.pop();
+            }
+            _ => {}
+        }
+    };
+    disable_raw_mode()?;
+    println!();
+    result.map(|_| secret)
+}
+
+fn load_history(path: &PathBuf) -> Vec<String> {
+    fs::read_to_string(path)
+        .map(|text| text.lines().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+fn common_prefix(candidates: &[String]) -> String {
+    let mut prefix = match candidates.first() {
+        Some(first) => first.clone(),
+        None => return String::new(),
+    };
+
+    for candidate in &candidates[1..] {
+        while !candidate.starts_with(&prefix) {
+            prefix.pop();
+        }
+    }
+    prefix
+}
+
+fn suspend(action: impl FnOnce() -> io::Result<()>) -> io::Result<()> {
+    disable_raw_mode()?;
+    action()?;
+    println!();
+    enable_raw_mode()
+}
+
+fn redraw(prompt: &str, buffer: &[char], cursor: usize) -> io::Result<()> {
+    let line: String = buffer.iter().collect();
+    execute!(
+        io::stdout(),
+        cursor::MoveToColumn(0),
+        Clear(ClearType::CurrentLine)
+    )?;
+    print!("{}{}", prompt, line);
+    execute!(
+        io::stdout(),
+        cursor::MoveToColumn((prompt.chars().count() + cursor) as u16)
+    )?;
+    io::stdout().flush()
+}
@@ -0,0 +1,159 @@
+use crate::dom;
+use markup5ever_rcdom::{Handle, NodeData};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+const REMOVED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "nav", "header", "footer", "aside", "form", "iframe", "svg",
+    "button", "select",
+];
+const UNLIKELY: &[&str] = &[
+    "banner",
+    "comment",
+    "community",
+    "cookie",
+    "disqus",
+    "footer",
+    "header",
+    "menu",
+    "modal",
+    "nav",
+    "popup",
+    "promo",
+    "related",
+    "share",
+    "sidebar",
+    "social",
+    "sponsor",
+    "advert",
+];
+const LIKELY: &[&str] = &[
+    "article", "body", "column", "content", "entry", "main", "post", "story",
+];
+const SCORED_TAGS: &[&str] = &["p", "pre", "td", "blockquote"];
+const MIN_PARAGRAPH_LEN: usize = 25;
+
+pub fn extract(html: &str) -> Option<String> {
+    let dom = dom::parse(html);
+    strip(&dom.document);
+
+    let mut scores: HashMap<*const markup5ever_rcdom::Node, (Handle, f64)> = HashMap::new();
+    score(&dom.document, &mut Vec::new(), &mut scores);
+
+    let (best, _) = scores
+        .into_values()
+        .map(|(node, score)| {
+            let density = 1.0 - link_density(&node);
+            let weighted = (score + class_weight(&node)) * density;
+            (node, weighted)
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+
+    let mut article = String::new();
+    if let Some(title) = dom::find_all(&dom.document, "title").first() {
+        let title = dom::text_of(title);
+        if !title.is_empty() && dom::find_all(&best, "h1").is_empty() {
+            article.push_str(&format!("<h1>{}</h1>", escape(&title)));
+        }
+    }
+    article.push_str(&dom::to_html(&best));
+    Some(article)
+}
+
+fn strip(node: &Handle) {
+    node.children
+        .borrow_mut()
+        .retain(|child| !is_boilerplate(child));
+    for child in node.children.borrow().iter() {
+        strip(child);
+    }
+}
+
+fn is_boilerplate(node: &Handle) -> bool {
+    let tag = match dom::tag_name(node) {
+        Some(tag) => tag,
+        None => return matches!(node.data, NodeData::Comment { .. }),
+    };
+    if REMOVED_TAGS.contains(&tag.as_str()) {
+        return true;
+    }
+    if tag == "body" || tag == "html" || tag == "article" || tag == "main" {
+        return false;
+    }
+    let names = class_and_id(node);
+    UNLIKELY.iter().any(|word| names.contains(word))
+        && !LIKELY.iter().any(|word| names.contains(word))
+}
+
+fn score(
+    node: &Handle,
+    ancestors: &mut Vec<Handle>,
+    scores: &mut HashMap<*const markup5ever_rcdom::Node, (Handle, f64)>,
+) {
+    if let Some(tag) = dom::tag_name(node) {
+        if SCORED_TAGS.contains(&tag.as_str()) {
+            let text = dom::text_of(node);
+            let len = text.chars().count();
+            if len >= MIN_PARAGRAPH_LEN {
+                let points = 1.0 + text.matches(',').count() as f64 + (len / 100).min(3) as f64;
+                let parents = ancestors.iter().rev().take(2);
+                for (depth, ancestor) in parents.enumerate() {
+                    let share = if depth == 0 { points } else { points / 2.0 };
+                    scores
+                        .entry(Rc::as_ptr(ancestor))
+                        .or_insert_with(|| (ancestor.clone(), 0.0))
+                        .1 += share;
+                }
+            }
+            return;
+        }
+    }
+
+    ancestors.push(node.clone());
+    for child in node.children.borrow().iter() {
+        score(child, ancestors, scores);
+    }
+    ancestors.pop();
+}
+
+fn class_and_id(node: &Handle) -> String {
+    let class = dom::attr(node, "class").unwrap_or_default();
+    let id = dom::attr(node, "id").unwrap_or_default();
+    format!("{} {}", class, id).to_lowercase()
+}
+
+fn class_weight(node: &Handle) -> f64 {
+    let names = class_and_id(node);
+    let mut weight = 0.0;
+    if LIKELY.iter().any(|word| names.contains(word)) {
+        weight += 25.0;
+    }
+    if UNLIKELY.iter().any(|word| names.contains(word)) {
+        weight -= 25.0;
+    }
+    if matches!(
+        dom::tag_name(node).as_deref(),
+        Some("article") | Some("main")
+    ) {
+        weight += 25.0;
+    }
+    weight
+}
+
+fn link_density(node: &Handle) -> f64 {
+    let total = dom::text_of(node).chars().count();
+    if total == 0 {
+        return 0.0;
+    }
+    let linked: usize = dom::find_all(node, "a")
+        .iter()
+        .map(|link| dom::text_of(link).chars().count())
+        .sum();
+    linked as f64 / total as f64
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
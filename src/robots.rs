@@ -0,0 +1,91 @@
+#[derive(Debug, Default)]
+pub struct Rules {
+    rules: Vec<(bool, String)>,
+}
+
+impl Rules {
+    pub fn parse(text: &str) -> Self {
+        let mut rules = Vec::new();
+        let mut in_group = false;
+        let mut group_started = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let (key, value) = match line.split_once(':') {
+                Some((key, value)) => (key.trim().to_ascii_lowercase(), value.trim()),
+                None => continue,
+            };
+            match key.as_str() {
+                "user-agent" => {
+                    if group_started {
+                        in_group = false;
+                        group_started = false;
+                    }
+                    in_group |= value == "*";
+                }
+                "allow" | "disallow" => {
+                    group_started = true;
+                    if in_group && !value.is_empty() {
+                        rules.push((key == "allow", value.to_string()));
+                    }
+                }
+                _ => {}
+            }
+        }
+        Rules { rules }
+    }
+
+    pub fn is_allowed(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|(_, pattern)| matches(pattern, path))
+            .max_by_key(|(allow, pattern)| (pattern.len(), *allow))
+            .is_none_or(|(allow, _)| *allow)
+    }
+}
+
+fn matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let mut rest = match path.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    if anchored {
+        match parts.pop().map(|last| rest.strip_suffix(last)) {
+            Some(Some(before)) => rest = before,
+            Some(None) => return false,
+            None => return rest.is_empty(),
+        }
+    }
+    for part in parts {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn longest_matching_rule_wins() {
+        let rules = Rules::parse(
+            "User-agent: googlebot\nDisallow: /\n\nUser-agent: *\nDisallow: /private\nAllow: /private/docs\nDisallow: /*.pdf$\n",
+        );
+        assert!(rules.is_allowed("/"));
+        assert!(!rules.is_allowed("/private/keys"));
+        assert!(rules.is_allowed("/private/docs/index.html"));
+        assert!(!rules.is_allowed("/files/report.pdf"));
+        assert!(rules.is_allowed("/files/report.pdf.html"));
+        assert!(!rules.is_allowed("/a.pdf.pdf"));
+    }
+}
@@ -0,0 +1,68 @@
+use regex::{Regex, RegexBuilder};
+
+pub enum Pattern {
+    Text { query: String, case_sensitive: bool },
+    Regex(Regex),
+}
+
+impl Pattern {
+    pub fn parse(input: &str, case_sensitive: bool) -> Result<Self, regex::Error> {
+        match input
+            .strip_prefix('/')
+            .and_then(|rest| rest.strip_suffix('/'))
+        {
+            Some(expression) if !expression.is_empty() => RegexBuilder::new(expression)
+                .case_insensitive(!case_sensitive)
+                .build()
+                .map(Pattern::Regex),
+            _ => Ok(Pattern::Text {
+                query: input.to_string(),
+                case_sensitive,
+            }),
+        }
+    }
+
+    pub fn ranges(&self, line: &str) -> Vec<(usize, usize)> {
+        match self {
+            Pattern::Regex(regex) => regex
+                .find_iter(line)
+                .filter(|m| !m.is_empty())
+                .map(|m| (m.start(), m.end()))
+                .collect(),
+            Pattern::Text {
+                query,
+                case_sensitive: true,
+            } => line
+                .match_indices(query.as_str())
+                .map(|(start, found)| (start, start + found.len()))
+                .collect(),
+            Pattern::Text { query, .. } => text_ranges(line, query),
+        }
+    }
+
+    pub fn is_match(&self, line: &str) -> bool {
+        !self.ranges(line).is_empty()
+    }
+}
+
+fn text_ranges(line: &str, query: &str) -> Vec<(usize, usize)> {
+    let lower_query = query.to_lowercase();
+    let mut ranges = Vec::new();
+    let mut next_start = 0;
+
+    if query.is_empty() {
+        return ranges;
+    }
+
+    for (start, _) in line.char_indices() {
+        let end = start + query.len();
+        if start < next_start || end > line.len() || !line.is_char_boundary(end) {
+            continue;
+        }
+        if line[start..end].to_lowercase() == lower_query {
+            ranges.push((start, end));
+            next_start = end;
+        }
+    }
+    ranges
+}
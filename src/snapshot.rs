@@ -0,0 +1,103 @@
+use crate::dom;
+use markup5ever_rcdom::{Handle, NodeData};
+use url::Url;
+
+const STYLE: &str = "body{max-width:42em;margin:2em auto;padding:0 1em;\
+font:17px/1.6 Georgia,serif;color:#222;background:#fdfdfb}\
+h1,h2,h3,h4{font-family:Helvetica,Arial,sans-serif;line-height:1.25}\
+a{color:#1a5fb4}img{max-width:100%;height:auto}\
+pre,code{font:14px/1.4 Menlo,Consolas,monospace;background:#f2f2f0}\
+pre{padding:.8em;overflow-x:auto}\
+blockquote{margin-left:0;padding-left:1em;border-left:3px solid #ccc;color:#555}\
+table{border-collapse:collapse}td,th{border:1px solid #ccc;padding:.3em .6em}\
+.source{font:13px Helvetica,Arial,sans-serif;color:#777;border-bottom:1px solid #ddd;\
+padding-bottom:.5em}";
+
+pub fn build(
+    article: &str,
+    title: &str,
+    url: &str,
+    saved: &str,
+    mut inline_image: impl FnMut(&str) -> Option<String>,
+) -> String {
+    let base = Url::parse(url).ok();
+    let dom = dom::parse(article);
+    let body = dom::find_all(&dom.document, "body").into_iter().next();
+    let root = body.unwrap_or_else(|| dom.document.clone());
+    rewrite(&root, base.as_ref(), &mut inline_image);
+
+    let content: String = root.children.borrow().iter().map(dom::to_html).collect();
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         <style>{STYLE}</style>\n</head>\n<body>\n\
+         <p class=\"source\">Saved from <a href=\"{url}\">{url_text}</a> on {saved}</p>\n\
+         {content}\n</body>\n</html>\n",
+        title = escape(title),
+        url = escape(url),
+        url_text = escape(url),
+        saved = escape(saved),
+        content = content,
+    )
+}
+
+fn rewrite(
+    node: &Handle,
+    base: Option<&Url>,
+    inline_image: &mut impl FnMut(&str) -> Option<String>,
+) {
+    if let NodeData::Element { name, attrs, .. } = &node.data {
+        let tag = name.local.as_ref().to_ascii_lowercase();
+        for attr in attrs.borrow_mut().iter_mut() {
+            let name = attr.name.local.as_ref().to_ascii_lowercase();
+            if name != "href" && name != "src" {
+                continue;
+            }
+            let absolute = match base.and_then(|base| base.join(&attr.value).ok()) {
+                Some(url) => url.to_string(),
+                None => continue,
+            };
+            let value = match (tag.as_str(), name.as_str()) {
+                ("img", "src") => inline_image(&absolute).unwrap_or(absolute),
+                _ => absolute,
+            };
+            attr.value = value.into();
+        }
+        attrs
+            .borrow_mut()
+            .retain(|attr| !attr.name.local.as_ref().eq_ignore_ascii_case("srcset"));
+    }
+    for child in node.children.borrow().iter() {
+        rewrite(child, base, inline_image);
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_are_absolute_and_images_inlined() {
+        let article = r#"<p><a href="/next">next</a> <img src="pic.png" srcset="a 2x"></p>"#;
+        let html = build(
+            article,
+            "A & B",
+            "https://example.com/post/1",
+            "2024-01-01",
+            |src| {
+                (src == "https://example.com/post/pic.png")
+                    .then(|| "data:image/png;base64,AA".into())
+            },
+        );
+        assert!(html.contains("<title>A &amp; B</title>"));
+        assert!(html.contains(r#"href="https://example.com/next""#));
+        assert!(html.contains(r#"src="data:image/png;base64,AA""#));
+        assert!(!html.contains("srcset"));
+    }
+}
@@ -0,0 +1,61 @@
+use crossterm::{
+    cursor, execute,
+    terminal::{self, Clear, ClearType},
+};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+const FRAMES: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+const FRAME_INTERVAL: Duration = Duration::from_millis(100);
+
+pub struct Spinner {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Spinner {
+    pub fn start(message: &str) -> Self {
+        let width = terminal::size().map(|(w, _)| w as usize).unwrap_or(80);
+        let message: String = message.chars().take(width.saturating_sub(3)).collect();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let handle = thread::spawn({
+            let stop = stop.clone();
+            move || {
+                let mut stdout = io::stdout();
+                let _ = execute!(stdout, cursor::Hide);
+                for frame in FRAMES.iter().cycle() {
+                    if stop.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    print!("\r{} {}", frame, message);
+                    let _ = stdout.flush();
+                    thread::sleep(FRAME_INTERVAL);
+                }
+                let _ = execute!(
+                    stdout,
+                    cursor::MoveToColumn(0),
+                    Clear(ClearType::CurrentLine),
+                    cursor::Show
+                );
+            }
+        });
+
+        Spinner {
+            stop,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use crate::dom;
+use html5ever::tendril::StrTendril;
+use html5ever::{local_name, namespace_url, ns, QualName};
+use markup5ever_rcdom::{Handle, Node, NodeData};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const MIN_COLUMN_WIDTH: usize = 3;
+const SKIPPED_CONTENT: &[&str] = &["table", "a", "form", "img"];
+
+pub fn align(html: &str, width: usize) -> String {
+    if !html.to_ascii_lowercase().contains("<table") {
+        return html.to_string();
+    }
+
+    let dom = dom::parse(html);
+    let mut replaced = false;
+    for table in dom::find_all(&dom.document, "table") {
+        if SKIPPED_CONTENT
+            .iter()
+            .any(|tag| !dom::find_all(&table, tag).is_empty())
+        {
+            continue;
+        }
+        let rows = rows(&table);
+        if rows.iter().all(|(cells, _)| cells.len() < 2) {
+            continue;
+        }
+        let mut text = String::new();
+        if let Some(caption) = dom::find_all(&table, "caption").first() {
+            text.push_str(&collapse(&dom::text_of(caption)));
+            text.push('\n');
+        }
+        text.push_str(&render(&rows, width));
+        replaced |= replace(&table, text);
+    }
+
+    if !replaced {
+        return html.to_string();
+    }
+    let children = dom.document.children.borrow();
+    children
+        .iter()
+        .filter(|child| dom::tag_name(child).is_some())
+        .map(dom::to_html)
+        .collect()
+}
+
+fn rows(table: &Handle) -> Vec<(Vec<String>, bool)> {
+    let mut rows = Vec::new();
+    for row in dom::find_all(table, "tr") {
+        let mut cells = Vec::new();
+        let mut header = true;
+        for cell in row.children.borrow().iter() {
+            let tag = dom::tag_name(cell);
+            if tag.as_deref() != Some("td") && tag.as_deref() != Some("th") {
+                continue;
+            }
+            header &= tag.as_deref() == Some("th");
+            cells.push(collapse(&dom::text_of(cell)));
+            let span = dom::attr(cell, "colspan").and_then(|span| span.parse::<usize>().ok());
+            for _ in 1..span.unwrap_or(1).min(100) {
+                cells.push(String::new());
+            }
+        }
+        if !cells.is_empty() {
+            rows.push((cells, header));
+        }
+    }
+    rows
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn render(rows: &[(Vec<String>, bool)], width: usize) -> String {
+    let columns = rows.iter().map(|(cells, _)| cells.len()).max().unwrap_or(0);
+    let mut widths = vec![0; columns];
+    for (cells, _) in rows {
+        for (i, cell) in cells.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    let available = width.saturating_sub(3 * columns + 1);
+    while widths.iter().sum::<usize>() > available {
+        let (widest, &current) = widths.iter().enumerate().max_by_key(|(_, w)| **w).unwrap();
+        if current <= MIN_COLUMN_WIDTH {
+            break;
+        }
+        widths[widest] = current - 1;
+    }
+
+    let mut lines = vec![border(&widths, '┌', '┬', '┐')];
+    for (i, (cells, header)) in rows.iter().enumerate() {
+        let mut line = String::from("│");
+        for (column, width) in widths.iter().enumerate() {
+            let cell = cells.get(column).map(String::as_str).unwrap_or("");
+            line.push_str(&format!(" {} │", pad(cell, *width)));
+        }
+        lines.push(line);
+        if *header && i + 1 < rows.len() {
+            lines.push(border(&widths, '├', '┼', '┤'));
+        }
+    }
+    lines.push(border(&widths, '└', '┴', '┘'));
+    lines.join("\n")
+}
+
+fn border(widths: &[usize], left: char, middle: char, right: char) -> String {
+    let segments: Vec<String> = widths.iter().map(|width| "─".repeat(width + 2)).collect();
+    format!("{}{}{}", left, segments.join(&middle.to_string()), right)
+}
+
+fn pad(cell: &str, width: usize) -> String {
+    let len = cell.chars().count();
+    if len > width {
+        let truncated: String = cell.chars().take(width.saturating_sub(1)).collect();
+        format!("{}…", truncated)
+    } else {
+        format!("{}{}", cell, " ".repeat(width - len))
+    }
+}
+
+fn replace(table: &Handle, text: String) -> bool {
+    let parent = match table.parent.take().and_then(|parent| parent.upgrade()) {
+        Some(parent) => parent,
+        None => return false,
+    };
+
+    let pre = Node::new(NodeData::Element {
+        name: QualName::new(None, ns!(html), local_name!("pre")),
+        attrs: RefCell::new(Vec::new()),
+        template_contents: RefCell::new(None),
+        mathml_annotation_xml_integration_point: false,
+    });
+    let contents = Node::new(NodeData::Text {
+        contents: RefCell::new(StrTendril::from(text)),
+    });
+    contents.parent.set(Some(Rc::downgrade(&pre)));
+    pre.children.borrow_mut().push(contents);
+    pre.parent.set(Some(Rc::downgrade(&parent)));
+
+    let mut children = parent.children.borrow_mut();
+    match children.iter().position(|child| Rc::ptr_eq(child, table)) {
+        Some(index) => {
+            children[index] = pre;
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_columns_are_truncated_to_fit() {
+        let rows = vec![
+            (vec!["Plan".to_string(), "Price".to_string()], true),
+            (
+                vec!["Enterprise unlimited".to_string(), "$99".to_string()],
+                false,
+            ),
+        ];
+        let table = render(&rows, 20);
+        assert!(table.lines().all(|line| line.chars().count() <= 20));
+        assert!(table.contains("│ Enterpr… │ $99   │"));
+        assert!(table.contains("├──────────┼───────┤"));
+    }
+}
@@ -0,0 +1,99 @@
+use crate::config::Colors;
+use crossterm::style::Color;
+
+pub const THEMES: &[&str] = &["dark", "light"];
+
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub name: String,
+    pub header_bg: Color,
+    pub header_fg: Color,
+    pub url_bg: Color,
+    pub url: Color,
+    pub heading: Color,
+    pub link: Color,
+    pub text: Color,
+    pub error: Color,
+    pub status_bg: Color,
+    pub status_fg: Color,
+    pub hint: Color,
+}
+
+impl Theme {
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Theme {
+                name: name.to_string(),
+                header_bg: Color::Blue,
+                header_fg: Color::White,
+                url_bg: Color::Black,
+                url: Color::Green,
+                heading: Color::Cyan,
+                link: Color::Blue,
+                text: Color::White,
+                error: Color::Red,
+                status_bg: Color::DarkGrey,
+                status_fg: Color::White,
+                hint: Color::DarkGrey,
+            }),
+            "light" => Some(Theme {
+                name: name.to_string(),
+                header_bg: Color::DarkBlue,
+                header_fg: Color::White,
+                url_bg: Color::Reset,
+                url: Color::DarkGreen,
+                heading: Color::DarkCyan,
+                link: Color::DarkBlue,
+                text: Color::Black,
+                error: Color::DarkRed,
+                status_bg: Color::Grey,
+                status_fg: Color::Black,
+                hint: Color::DarkGrey,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn load(name: &str, colors: &Colors) -> Result<Self, String> {
+        let mut theme = Self::preset(name).ok_or_else(|| {
+            format!(
+                "unknown theme '{}' (available: {})",
+                name,
+                THEMES.join(", ")
+            )
+        })?;
+        let overrides = [
+            (&mut theme.header_bg, colors.header_bg),
+            (&mut theme.header_fg, colors.header_fg),
+            (&mut theme.url, colors.url),
+            (&mut theme.heading, colors.heading),
+            (&mut theme.link, colors.link),
+            (&mut theme.text, colors.text),
+            (&mut theme.status_bg, colors.status_bg),
+            (&mut theme.status_fg, colors.status_fg),
+        ];
+        for (slot, color) in overrides {
+            if let Some(color) = color {
+                *slot = color;
+            }
+        }
+        Ok(theme)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_override_the_preset() {
+        let colors = Colors {
+            link: Some(Color::Magenta),
+            ..Colors::default()
+        };
+        let theme = Theme::load("light", &colors).unwrap();
+        assert_eq!(theme.link, Color::Magenta);
+        assert_eq!(theme.heading, Color::DarkCyan);
+        assert!(Theme::load("solarized", &colors).is_err());
+    }
+}
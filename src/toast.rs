@@ -0,0 +1,105 @@
+use crossterm::{
+    cursor,
+    event::{poll, read},
+    execute,
+    style::{Color, ResetColor, SetBackgroundColor, SetForegroundColor},
+    terminal::{self, disable_raw_mode, enable_raw_mode},
+};
+use std::io::{self, IsTerminal, Write};
+use std::time::Duration;
+
+const TOAST_DURATION: Duration = Duration::from_millis(2500);
+const MARGIN: usize = 8;
+
+pub fn show(message: &str, background: Color) -> io::Result<()> {
+    if !io::stdin().is_terminal() {
+        println!("{}", message);
+        return Ok(());
+    }
+
+    let (width, height) = terminal::size()?;
+    let inner = (width as usize).saturating_sub(MARGIN).max(1);
+    let lines = wrap(message, inner);
+    let box_width = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0)
+        + 4;
+    let left = (width as usize).saturating_sub(box_width) / 2;
+    let top = (height as usize).saturating_sub(lines.len() + 2) / 2;
+
+    execute!(
+        io::stdout(),
+        SetBackgroundColor(background),
+        SetForegroundColor(Color::White)
+    )?;
+    let blank = " ".repeat(box_width);
+    execute!(io::stdout(), cursor::MoveTo(left as u16, top as u16))?;
+    print!("{}", blank);
+    for (i, line) in lines.iter().enumerate() {
+        execute!(
+            io::stdout(),
+            cursor::MoveTo(left as u16, (top + i + 1) as u16)
+        )?;
+        print!("  {:<width$}  ", line, width = box_width - 4);
+    }
+    execute!(
+        io::stdout(),
+        cursor::MoveTo(left as u16, (top + lines.len() + 1) as u16)
+    )?;
+    print!("{}", blank);
+    execute!(io::stdout(), ResetColor)?;
+    io::stdout().flush()?;
+
+    enable_raw_mode()?;
+    let dismissed = match poll(TOAST_DURATION) {
+        Ok(true) => read().map(|_| ()),
+        Ok(false) => Ok(()),
+        Err(e) => Err(e),
+    };
+    disable_raw_mode()?;
+    dismissed
+}
+
+fn wrap(message: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in message.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+    lines
+        .into_iter()
+        .flat_map(|line| {
+            let chars: Vec<char> = line.chars().collect();
+            if chars.is_empty() {
+                return vec![String::new()];
+            }
+            chars
+                .chunks(width)
+                .map(|chunk| chunk.iter().collect())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_breaks_on_words_and_splits_long_ones() {
+        assert_eq!(wrap("Bookmark deleted!", 40), vec!["Bookmark deleted!"]);
+        assert_eq!(wrap("one two three", 8), vec!["one two", "three"]);
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+    }
+}
@@ -0,0 +1,134 @@
+const INDENT: &str = "  ";
+
+enum Token<'a> {
+    Open(&'a str),
+    Close(&'a str),
+    Empty(&'a str),
+    Text(&'a str),
+}
+
+pub fn pretty(source: &str) -> String {
+    let tokens = tokenize(source);
+    let mut lines = Vec::new();
+    let mut depth: usize = 0;
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let indent = INDENT.repeat(depth);
+        match &tokens[i] {
+            Token::Open(tag) => {
+                if let (Some(Token::Text(text)), Some(Token::Close(close))) =
+                    (tokens.get(i + 1), tokens.get(i + 2))
+                {
+                    if !text.contains('\n') && tag_name(close) == tag_name(tag) {
+                        lines.push(format!("{}{}{}{}", indent, tag, text, close));
+                        i += 3;
+                        continue;
+                    }
+                }
+                if let Some(Token::Close(close)) = tokens.get(i + 1) {
+                    if tag_name(close) == tag_name(tag) {
+                        lines.push(format!("{}{}{}", indent, tag, close));
+                        i += 2;
+                        continue;
+                    }
+                }
+                lines.push(format!("{}{}", indent, tag));
+                depth += 1;
+            }
+            Token::Close(tag) => {
+                depth = depth.saturating_sub(1);
+                lines.push(format!("{}{}", INDENT.repeat(depth), tag));
+            }
+            Token::Empty(tag) => lines.push(format!("{}{}", indent, tag)),
+            Token::Text(text) => {
+                for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+                    lines.push(format!("{}{}", indent, line));
+                }
+            }
+        }
+        i += 1;
+    }
+    lines.join("\n")
+}
+
+fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+
+    while !rest.is_empty() {
+        if !rest.starts_with('<') {
+            let end = rest.find('<').unwrap_or(rest.len());
+            let text = rest[..end].trim();
+            if !text.is_empty() {
+                tokens.push(Token::Text(text));
+            }
+            rest = &rest[end..];
+            continue;
+        }
+
+        let end = if rest.starts_with("<!--") {
+            rest.find("-->").map(|i| i + 3)
+        } else if rest.starts_with("<![CDATA[") {
+            rest.find("]]>").map(|i| i + 3)
+        } else {
+            tag_end(rest)
+        };
+        let end = end.unwrap_or(rest.len());
+        let tag = &rest[..end];
+        tokens.push(if tag.starts_with("</") {
+            Token::Close(tag)
+        } else if tag.starts_with("<?") || tag.starts_with("<!") || tag.ends_with("/>") {
+            Token::Empty(tag)
+        } else {
+            Token::Open(tag)
+        });
+        rest = &rest[end..];
+    }
+    tokens
+}
+
+fn tag_end(tag: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in tag.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '>') => return Some(i + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn tag_name(tag: &str) -> &str {
+    let tag = tag.trim_start_matches('<').trim_start_matches('/');
+    let end = tag
+        .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .unwrap_or(tag.len());
+    &tag[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_elements_are_indented() {
+        let source = r#"<?xml version="1.0"?><urlset><url><loc>https://example.com/</loc><meta a="x>y"/></url><empty></empty></urlset>"#;
+        assert_eq!(
+            pretty(source),
+            [
+                r#"<?xml version="1.0"?>"#,
+                "<urlset>",
+                "  <url>",
+                "    <loc>https://example.com/</loc>",
+                r#"    <meta a="x>y"/>"#,
+                "  </url>",
+                "  <empty></empty>",
+                "</urlset>",
+            ]
+            .join("\n")
+        );
+    }
+}